@@ -23,6 +23,19 @@ FEATURES=(
     "humantime regex"
     "reltime regex"
     "humantime reltime regex"
+    "kv"
+    "humantime kv"
+    "reltime kv"
+    "regex kv"
+    "humantime reltime kv"
+    "humantime regex kv"
+    "reltime regex kv"
+    "humantime reltime regex kv"
+    "journald"
+    "kv journald"
+    "wrap"
+    "kv wrap"
+    "humantime reltime regex kv journald wrap"
 )
 
 
@@ -31,6 +31,7 @@ fn main() {
     warn!("o_O");
     error!("boom");
     self::longer_nested::deep();
+    info!("a message\nspanning several\nlines");
     debug!("some nice message to help debugging");
     info!("such information");
     warn!("o_O");
@@ -0,0 +1,17 @@
+extern crate lovely_env_logger;
+#[macro_use]
+extern crate log;
+
+fn main() {
+    lovely_env_logger::init_default();
+
+    if !log_enabled!(log::Level::Trace) {
+        eprintln!("To see the full demo, try setting `RUST_LOG=log_kv=trace`.");
+        return;
+    }
+
+    info!(user_id = 42, name = "Jane Doe"; "login");
+    debug!(query = "SELECT 1", rows = 1, elapsed_ms = 0.42; "query done");
+    warn!(retries = 3; "connection lost,\nretrying");
+    error!("no key-values");
+}
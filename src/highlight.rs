@@ -0,0 +1,481 @@
+//! Highlighting of messages, as set through `Config::highlight_values` and
+//! `Config::highlight_rules`, or the `RUST_LOG_HIGHLIGHT_VALUES` and
+//! `RUST_LOG_HIGHLIGHT` environment variables.
+
+use std::io::{self, Write};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::ops::Range;
+
+use env_logger::fmt::style::Style;
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use crate::Theme;
+
+/// A rule highlighting the parts of the messages matching a regular
+/// expression.
+///
+/// Lines not matching any rule can be dimmed, to find the interesting lines
+/// of a busy log at a glance, without losing their context:
+///
+/// ```
+/// use lovely_env_logger::env_logger::fmt::style::{AnsiColor, Style};
+/// use lovely_env_logger::{Config, HighlightRule};
+///
+/// let config = Config {
+///     highlight_rules: vec![HighlightRule {
+///         regex: regex::Regex::new(r"user_id=\d+").unwrap(),
+///         style: Style::new().fg_color(Some(AnsiColor::Cyan.into())),
+///     }],
+///     dim_unmatched: true,
+///     ..Config::default()
+/// };
+/// ```
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+pub struct HighlightRule {
+    /// The regular expression matching the parts of the messages to
+    /// highlight
+    pub regex: Regex,
+    /// The style of the matching parts
+    pub style: Style,
+}
+
+#[cfg(feature = "regex")]
+impl HighlightRule {
+    /// Parses rules such as `timeout|refused=red,bold;user_id=\d+=cyan`.
+    ///
+    /// Rules are separated by `;`. The style of each rule follows its last
+    /// `=`. Invalid rules are ignored.
+    pub(crate) fn parse_all(spec: &str) -> Vec<Self> {
+        spec.split(';')
+            .filter_map(|rule| {
+                let (pattern, style) = rule.rsplit_once('=')?;
+                Some(Self {
+                    regex: Regex::new(pattern).ok()?,
+                    style: crate::theme::parse_style_names(style)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Returns the parts of a message matching highlighting rules, with their
+/// styles. The first rule wins when several rules match the same part.
+#[cfg(feature = "regex")]
+pub(crate) fn match_rules(rules: &[HighlightRule], text: &str) -> Vec<(Range<usize>, Style)> {
+    let mut spans = Vec::new();
+    for rule in rules {
+        for found in rule.regex.find_iter(text) {
+            if !found.is_empty() {
+                insert(&mut spans, found.range(), rule.style);
+            }
+        }
+    }
+    spans
+}
+
+/// Adds a span to sorted spans, unless it overlaps one of them
+fn insert(spans: &mut Vec<(Range<usize>, Style)>, range: Range<usize>, style: Style) {
+    let index = spans.partition_point(|(span, _)| span.start < range.start);
+    let overlaps_previous = index > 0 && spans[index - 1].0.end > range.start;
+    let overlaps_next = spans
+        .get(index)
+        .is_some_and(|(span, _)| span.start < range.end);
+    if !overlaps_previous && !overlaps_next {
+        spans.insert(index, (range, style));
+    }
+}
+
+/// A message, with the styles of its parts
+pub(crate) struct Highlighted<'a> {
+    /// The message
+    text: &'a str,
+    /// Style of the parts of the message not highlighted
+    style: Style,
+    /// Highlighted parts of the message, sorted and not overlapping
+    spans: Vec<(Range<usize>, Style)>,
+}
+
+impl<'a> Highlighted<'a> {
+    /// A message with some highlighted parts, sorted and not overlapping
+    pub(crate) fn new(text: &'a str, style: Style, spans: Vec<(Range<usize>, Style)>) -> Self {
+        Self { text, style, spans }
+    }
+
+    /// Highlights the numbers, durations, quoted strings, file paths, IP
+    /// addresses and URLs of the message, outside of the parts already
+    /// highlighted
+    pub(crate) fn highlight_values(&mut self, theme: &Theme) {
+        for (range, style) in scan(self.text, theme) {
+            insert(&mut self.spans, range, style);
+        }
+    }
+
+    /// Returns the message
+    #[inline]
+    pub(crate) fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Writes a part of the message, which must be a slice of its text
+    pub(crate) fn write(&self, out: &mut impl Write, part: &str) -> io::Result<()> {
+        let start = part.as_ptr() as usize - self.text.as_ptr() as usize;
+        let end = start + part.len();
+        let mut position = start;
+        for (range, style) in &self.spans {
+            if range.end <= position {
+                continue;
+            }
+            if range.start >= end {
+                break;
+            }
+            let span_start = range.start.max(position);
+            let span_end = range.end.min(end);
+            write_styled(out, &self.text[position..span_start], self.style)?;
+            write_styled(out, &self.text[span_start..span_end], *style)?;
+            position = span_end;
+        }
+        write_styled(out, &self.text[position..end], self.style)
+    }
+}
+
+/// Writes some text with a style
+fn write_styled(out: &mut impl Write, text: &str, style: Style) -> io::Result<()> {
+    if text.is_empty() {
+        return Ok(());
+    }
+    write!(out, "{}{}{}", style.render(), text, style.render_reset())
+}
+
+/// Returns the values found in a message, with their styles
+fn scan(text: &str, theme: &Theme) -> Vec<(Range<usize>, Style)> {
+    let bytes = text.as_bytes();
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        // Values are only looked for at the start of words
+        let found = if i == 0 || !is_word(bytes[i - 1]) {
+            url(bytes, i)
+                .map(|end| (end, theme.url))
+                .or_else(|| string(bytes, i).map(|end| (end, theme.string)))
+                .or_else(|| path(bytes, i).map(|end| (end, theme.path)))
+                .or_else(|| address(text, i).map(|end| (end, theme.address)))
+                .or_else(|| match number(text, i)? {
+                    (end, true) => Some((end, theme.duration)),
+                    (end, false) => Some((end, theme.number)),
+                })
+        } else {
+            None
+        };
+        match found {
+            Some((end, style)) => {
+                spans.push((i..end, style));
+                i = end;
+            }
+            None if is_word(bytes[i]) => {
+                // Skip the rest of the word, including dotted parts as in
+                // `v1.2.3`
+                while i < bytes.len()
+                    && (is_word(bytes[i])
+                        || bytes[i] == b'.' && bytes.get(i + 1).is_some_and(|&byte| is_word(byte)))
+                {
+                    i += 1;
+                }
+            }
+            None => i += 1,
+        }
+    }
+    spans
+}
+
+/// Whether a byte is part of a word. Bytes of non-ASCII characters are, so
+/// that words are never split in the middle of a character.
+#[inline]
+fn is_word(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+/// Returns the end of the run of bytes matching a predicate, from `start`
+#[inline]
+fn run(bytes: &[u8], start: usize, predicate: impl Fn(u8) -> bool) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&byte| !predicate(byte))
+        .map_or(bytes.len(), |len| start + len)
+}
+
+/// Whether a value ending at `end` is followed by the end of its word
+#[inline]
+fn ends_word(bytes: &[u8], end: usize) -> bool {
+    bytes.get(end).is_none_or(|&byte| !is_word(byte))
+}
+
+/// Matches a URL such as `https://example.com/path?query`
+fn url(bytes: &[u8], start: usize) -> Option<usize> {
+    if !bytes[start].is_ascii_alphabetic() {
+        return None;
+    }
+    let scheme_end = run(bytes, start, |byte| {
+        byte.is_ascii_alphanumeric() || matches!(byte, b'+' | b'.' | b'-')
+    });
+    if !bytes[scheme_end..].starts_with(b"://") {
+        return None;
+    }
+    let end = run(bytes, scheme_end + 3, |byte| {
+        byte.is_ascii_graphic() && !matches!(byte, b'"' | b'\'' | b'`' | b'<' | b'>')
+    });
+    // Punctuation ending a sentence is not part of the URL
+    let end = trim_end(bytes, scheme_end + 3, end, b".,;:!?)]}");
+    (end > scheme_end + 3).then_some(end)
+}
+
+/// Matches a string between double quotes, single quotes or backticks
+fn string(bytes: &[u8], start: usize) -> Option<usize> {
+    let quote = bytes[start];
+    if !matches!(quote, b'"' | b'\'' | b'`') {
+        return None;
+    }
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' => return None,
+            byte if byte == quote => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Matches a file path starting with `/`, `./`, `../` or `~/`, or a relative
+/// path with an extension such as `src/main.rs`, followed by line and column
+/// numbers if any
+fn path(bytes: &[u8], start: usize) -> Option<usize> {
+    let is_path = |byte: u8| {
+        byte.is_ascii_alphanumeric()
+            || matches!(byte, b'/' | b'.' | b'_' | b'-' | b'~' | b'+' | b'@')
+    };
+    let end = run(bytes, start, is_path);
+    let end = trim_end(bytes, start, end, b".");
+    let path = &bytes[start..end];
+    let rooted = [&b"/"[..], b"./", b"../", b"~/"]
+        .iter()
+        .any(|prefix| path.starts_with(prefix));
+    let has_extension = path.contains(&b'/')
+        && path
+            .rsplit(|&byte| byte == b'/')
+            .next()
+            .is_some_and(|name| {
+                name.rsplit(|&byte| byte == b'.')
+                    .next()
+                    .is_some_and(|extension| {
+                        extension.len() + 1 < name.len()
+                            && (1..=5).contains(&extension.len())
+                            && extension[0].is_ascii_alphabetic()
+                    })
+            });
+    if !(rooted || has_extension) || !path.iter().any(u8::is_ascii_alphanumeric) {
+        return None;
+    }
+    // Line and column numbers, as in `src/main.rs:42:7`
+    let mut end = end;
+    for _ in 0..2 {
+        match bytes.get(end) {
+            Some(b':') if bytes.get(end + 1).is_some_and(u8::is_ascii_digit) => {
+                end = run(bytes, end + 1, |byte| byte.is_ascii_digit());
+            }
+            _ => break,
+        }
+    }
+    ends_word(bytes, end).then_some(end)
+}
+
+/// Matches an IPv4 or IPv6 address, followed by a port if any
+fn address(text: &str, start: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let v4_end = run(bytes, start, |byte| byte.is_ascii_digit() || byte == b'.');
+    let v4_end = trim_end(bytes, start, v4_end, b".");
+    let end = if text[start..v4_end].parse::<Ipv4Addr>().is_ok() {
+        match bytes.get(v4_end) {
+            Some(b':') if bytes.get(v4_end + 1).is_some_and(u8::is_ascii_digit) => {
+                run(bytes, v4_end + 1, |byte| byte.is_ascii_digit())
+            }
+            _ => v4_end,
+        }
+    } else {
+        let v6_end = run(bytes, start, |byte| {
+            byte.is_ascii_hexdigit() || byte == b':' || byte == b'.'
+        });
+        let v6_end = trim_end(bytes, start, v6_end, b".:");
+        // Not a path such as `a::b`
+        if !bytes[start..v6_end].iter().any(u8::is_ascii_digit) {
+            return None;
+        }
+        text[start..v6_end].parse::<Ipv6Addr>().ok()?;
+        v6_end
+    };
+    ends_word(bytes, end).then_some(end)
+}
+
+/// Units of the durations, longest first
+const DURATION_UNITS: [&str; 10] = ["min", "ms", "ns", "us", "µs", "μs", "s", "m", "h", "d"];
+
+/// Matches a number, or a duration such as `1.5s` or `1h30m`. Returns the end
+/// of the match, and whether it is a duration.
+fn number(text: &str, start: usize) -> Option<(usize, bool)> {
+    let bytes = text.as_bytes();
+    let mut end = number_literal(bytes, start)?;
+    let mut is_duration = false;
+    // Durations may be made of several parts, as `1h30m`
+    while let Some(unit) = DURATION_UNITS
+        .iter()
+        .find(|unit| text[end..].starts_with(*unit))
+    {
+        end += unit.len();
+        is_duration = true;
+        if !bytes.get(end).is_some_and(u8::is_ascii_digit) {
+            break;
+        }
+        end = number_literal(bytes, end)?;
+    }
+    ends_word(bytes, end).then_some((end, is_duration))
+}
+
+/// Matches a numeric literal, such as `-42`, `1_000`, `0x1f`, `3.14`,
+/// `6.02e23` or a version number such as `1.2.3`
+fn number_literal(bytes: &[u8], start: usize) -> Option<usize> {
+    let digits = |i: usize| run(bytes, i, |byte| byte.is_ascii_digit() || byte == b'_');
+    let is_digit = |i: usize| bytes.get(i).is_some_and(u8::is_ascii_digit);
+    let mut i = start;
+    if bytes[i] == b'-' {
+        i += 1;
+    }
+    if !is_digit(i) {
+        return None;
+    }
+    if bytes[i..].starts_with(b"0x") && bytes.get(i + 2).is_some_and(u8::is_ascii_hexdigit) {
+        return Some(run(bytes, i + 2, |byte| {
+            byte.is_ascii_hexdigit() || byte == b'_'
+        }));
+    }
+    i = digits(i);
+    while bytes.get(i) == Some(&b'.') && is_digit(i + 1) {
+        i = digits(i + 1);
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        let exponent = match bytes.get(i + 1) {
+            Some(b'+' | b'-') => i + 2,
+            _ => i + 1,
+        };
+        if is_digit(exponent) {
+            i = digits(exponent);
+        }
+    }
+    Some(i)
+}
+
+/// Returns `end`, moved back over the given trailing bytes
+fn trim_end(bytes: &[u8], start: usize, mut end: usize, trailing: &[u8]) -> usize {
+    while end > start && trailing.contains(&bytes[end - 1]) {
+        end -= 1;
+    }
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use log::Level;
+
+    use crate::tests::Capture;
+    use crate::{Config, Theme};
+
+    #[test]
+    fn values_are_highlighted_with_colors_only() {
+        let message = "fetched 42 rows of \"users\" from https://example.com/api in 1.5s";
+        let config = || Config {
+            highlight_values: true,
+            ..Config::default()
+        };
+        let theme = Theme::default();
+        let styled = |style: env_logger::fmt::style::Style, text: &str| {
+            format!("{}{text}{}", style.render(), style.render_reset())
+        };
+
+        let capture = Capture::colored(config());
+        capture.log(Level::Info, message);
+        let line = capture.output();
+        assert!(line.contains(&styled(theme.number, "42")), "{line:?}");
+        assert!(
+            line.contains(&styled(theme.string, "\"users\"")),
+            "{line:?}"
+        );
+        assert!(line.contains(&styled(theme.url, "https://example.com/api")));
+        assert!(line.contains(&styled(theme.duration, "1.5s")));
+
+        let capture = Capture::new(config());
+        capture.log(Level::Info, message);
+        assert_eq!(capture.output(), format!("INFO  app {message}\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn rules_are_parsed() {
+        use env_logger::fmt::style::{AnsiColor, Style};
+
+        use super::HighlightRule;
+
+        let color = |color: AnsiColor| Style::new().fg_color(Some(color.into()));
+        let rules = HighlightRule::parse_all(r"timeout|refused=red,bold;user_id=\d+=cyan");
+        let rules: Vec<_> = rules
+            .iter()
+            .map(|rule| (rule.regex.as_str(), rule.style))
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                ("timeout|refused", color(AnsiColor::Red).bold()),
+                (r"user_id=\d+", color(AnsiColor::Cyan)),
+            ]
+        );
+
+        // Invalid patterns, missing or invalid styles
+        let rules = HighlightRule::parse_all("(unclosed=red;no style;x=purple;;ok=on_bright_blue");
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].regex.as_str(), "ok");
+        assert_eq!(
+            rules[0].style,
+            Style::new().bg_color(Some(AnsiColor::BrightBlue.into()))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn unmatched_lines_are_dimmed() {
+        use env_logger::fmt::style::{AnsiColor, Style};
+
+        use super::HighlightRule;
+
+        let capture = Capture::colored(Config {
+            highlight_rules: vec![HighlightRule {
+                regex: regex::Regex::new(r"user_id=\d+").unwrap(),
+                style: Style::new().fg_color(Some(AnsiColor::Cyan.into())),
+            }],
+            dim_unmatched: true,
+            ..Config::default()
+        });
+        capture.log(Level::Info, "login of user_id=42");
+        capture.log(Level::Info, "cache refreshed");
+
+        let output = capture.output();
+        let mut lines = output.lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .ends_with("login of \x1b[36muser_id=42\x1b[0m"));
+        assert_eq!(
+            lines.next(),
+            Some("\x1b[2mINFO \x1b[0m \x1b[2mapp\x1b[0m \x1b[2mcache refreshed\x1b[0m")
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Clickable OSC 8 hyperlinks on the file names and line numbers, as set
+//! through `Config::hyperlinks` or the `RUST_LOG_HYPERLINKS` and
+//! `RUST_LOG_EDITOR_URL` environment variables.
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Write;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Builds the targets of the hyperlinks
+pub(crate) struct Hyperlinks {
+    /// Editor URL template, such as `vscode://file/{path}:{line}`
+    editor_url: Option<String>,
+    /// Host name used in `file://` URLs
+    hostname: String,
+    /// Directory relative file names are resolved from
+    current_dir: PathBuf,
+    /// Encoded paths of the file names seen so far, resolved once each
+    paths: Mutex<HashMap<String, String>>,
+}
+
+impl Hyperlinks {
+    /// Returns the hyperlinks builder, when hyperlinks are enabled.
+    ///
+    /// When `enabled` is `None`, hyperlinks are enabled when standard error is
+    /// a terminal known to support them.
+    pub(crate) fn new(enabled: Option<bool>, editor_url: Option<&str>) -> Option<Self> {
+        let enabled =
+            enabled.unwrap_or_else(|| std::io::stderr().is_terminal() && terminal_supports_them());
+        if !enabled {
+            return None;
+        }
+        Some(Self {
+            editor_url: editor_url.map(str::to_owned),
+            hostname: crate::hostname().unwrap_or_default(),
+            current_dir: env::current_dir().unwrap_or_default(),
+            paths: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Returns the URL pointing at a line of a file
+    pub(crate) fn url(&self, file: &str, line: Option<u32>) -> String {
+        let mut paths = self.paths.lock().unwrap();
+        let path = match paths.get(file) {
+            Some(path) => path,
+            None => paths.entry(file.to_owned()).or_insert(self.resolve(file)),
+        };
+        match self.editor_url {
+            Some(ref template) => template
+                .replace("{path}", path)
+                .replace("{line}", &line.unwrap_or(1).to_string()),
+            None => format!("file://{}{path}", self.hostname),
+        }
+    }
+
+    /// Returns the encoded absolute path of a file, following symbolic links
+    fn resolve(&self, file: &str) -> String {
+        let mut path = self.current_dir.join(file);
+        if let Ok(canonical) = path.canonicalize() {
+            path = canonical;
+        }
+        encode_path(&path)
+    }
+}
+
+/// Whether the terminal is known to support hyperlinks
+fn terminal_supports_them() -> bool {
+    if let Some(force) = env::var_os("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+    if env::var_os("WT_SESSION").is_some() || env::var_os("KONSOLE_VERSION").is_some() {
+        return true;
+    }
+    if let Ok(version) = env::var("VTE_VERSION") {
+        // Supported since VTE 0.50
+        return version.parse::<u32>().is_ok_and(|version| version >= 5000);
+    }
+    if let Ok(program) = env::var("TERM_PROGRAM") {
+        if ["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper"].contains(&program.as_str()) {
+            return true;
+        }
+    }
+    env::var("TERM").is_ok_and(|term| {
+        [
+            "xterm-kitty",
+            "foot",
+            "alacritty",
+            "wezterm",
+            "xterm-ghostty",
+        ]
+        .iter()
+        .any(|name| term.starts_with(name))
+    })
+}
+
+/// Percent-encodes a path to be used in a URL
+fn encode_path(path: &Path) -> String {
+    let mut encoded = String::new();
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => {
+                let _ = write!(encoded, "%{byte:02X}");
+            }
+        }
+    }
+    encoded
+}
+
+/// Struct to display text as a OSC 8 hyperlink
+pub(crate) struct Hyperlink<'a, T> {
+    /// Target of the link
+    pub(crate) url: &'a str,
+    /// Displayed text
+    pub(crate) text: T,
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for Hyperlink<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", self.url, self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{encode_path, Hyperlinks};
+
+    #[test]
+    fn paths_are_encoded() {
+        assert_eq!(encode_path(Path::new("/src/main.rs")), "/src/main.rs");
+        assert_eq!(encode_path(Path::new("/a b/é#1")), "/a%20b/%C3%A9%231");
+    }
+
+    #[test]
+    fn paths_are_resolved_once() {
+        let hyperlinks = Hyperlinks::new(Some(true), Some("editor://{path}:{line}")).unwrap();
+        let path = encode_path(&Path::new("src/lib.rs").canonicalize().unwrap());
+        assert_eq!(
+            hyperlinks.url("src/lib.rs", Some(42)),
+            format!("editor://{path}:42")
+        );
+        assert_eq!(
+            hyperlinks.url("src/lib.rs", None),
+            format!("editor://{path}:1")
+        );
+        assert_eq!(hyperlinks.paths.lock().unwrap().len(), 1);
+    }
+}
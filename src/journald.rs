@@ -0,0 +1,320 @@
+//! Native systemd-journald output, as set through `Config::journald` or the
+//! `RUST_LOG_JOURNALD` and `RUST_LOG_JOURNALD_SOCKET` environment variables.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+
+use env_logger::fmt::Formatter;
+
+use crate::syslog::severity;
+
+/// Default path of the socket of systemd-journald
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// When the native journald protocol is used
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JournaldMode {
+    /// Never
+    Off,
+    /// When standard error is connected to the journal, as told by the
+    /// `JOURNAL_STREAM` environment variable, and the output is
+    /// [`Output::Pretty`](crate::Output::Pretty).
+    ///
+    /// Only the `init` functions do it. Builders returned by
+    /// [`formatted_builder`](crate::formatted_builder) may have their target
+    /// overridden, so they treat this mode as [`JournaldMode::Off`].
+    #[default]
+    Auto,
+    /// Always
+    Always,
+}
+
+impl JournaldMode {
+    /// Parses `0`, `auto` or `1`
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "0" => Some(Self::Off),
+            "auto" => Some(Self::Auto),
+            "1" => Some(Self::Always),
+            _ => None,
+        }
+    }
+}
+
+/// Configuration of the native journald output.
+///
+/// Each log is sent as a set of structured fields: `MESSAGE`, `PRIORITY`,
+/// `CODE_FILE`, `CODE_LINE`, `CODE_MODULE`, `TARGET`, and the key-values of
+/// the log, with their keys in uppercase.
+///
+/// ```
+/// use std::os::unix::net::UnixDatagram;
+///
+/// use log::Log;
+/// use lovely_env_logger::{Config, Journald, JournaldMode};
+///
+/// let path = std::env::temp_dir().join(format!("lovely-journald-{}", std::process::id()));
+/// let socket = UnixDatagram::bind(&path).unwrap();
+///
+/// let logger = lovely_env_logger::formatted_builder(Config {
+///     journald: Journald {
+///         mode: JournaldMode::Always,
+///         socket: path.clone(),
+///     },
+///     ..Config::default()
+/// })
+/// .filter_level(log::LevelFilter::Info)
+/// .build();
+/// logger.log(
+///     &log::Record::builder()
+///         .level(log::Level::Warn)
+///         .target("app::db")
+///         .module_path(Some("app::db"))
+///         .file(Some("src/db.rs"))
+///         .line(Some(42))
+///         .args(format_args!("connection lost\nretrying"))
+///         .build(),
+/// );
+///
+/// let mut buf = [0; 1024];
+/// let len = socket.recv(&mut buf).unwrap();
+/// let mut expected = b"PRIORITY=4\nMESSAGE\n".to_vec();
+/// expected.extend(24u64.to_le_bytes());
+/// expected.extend(b"connection lost\nretrying\n");
+/// expected.extend(b"CODE_FILE=src/db.rs\nCODE_LINE=42\nCODE_MODULE=app::db\nTARGET=app::db\n");
+/// assert_eq!(&buf[..len], &expected[..]);
+/// std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Journald {
+    /// When the native journald protocol is used
+    pub mode: JournaldMode,
+    /// Path of the socket of systemd-journald
+    pub socket: PathBuf,
+}
+
+impl Default for Journald {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            mode: JournaldMode::Auto,
+            socket: PathBuf::from(JOURNALD_SOCKET),
+        }
+    }
+}
+
+impl Journald {
+    /// Connects to journald, when the native protocol should be used
+    pub(crate) fn connect(&self, pretty: bool) -> Option<JournaldSocket> {
+        let enabled = match self.mode {
+            JournaldMode::Off => false,
+            JournaldMode::Auto => pretty && crate::systemd::stderr_is_journal(),
+            JournaldMode::Always => true,
+        };
+        if !enabled {
+            return None;
+        }
+        let socket = UnixDatagram::unbound().ok()?;
+        socket.connect(&self.socket).ok()?;
+        Some(JournaldSocket(socket))
+    }
+}
+
+/// Writes a record as journald fields
+pub(crate) fn write(f: &mut Formatter, record: &log::Record) -> io::Result<()> {
+    write_field(f, "PRIORITY", severity(record.level()))?;
+    write_field(f, "MESSAGE", record.args())?;
+    if let Some(file) = record.file() {
+        write_field(f, "CODE_FILE", file)?;
+    }
+    if let Some(line) = record.line() {
+        write_field(f, "CODE_LINE", line)?;
+    }
+    if let Some(module_path) = record.module_path() {
+        write_field(f, "CODE_MODULE", module_path)?;
+    }
+    write_field(f, "TARGET", record.target())?;
+    #[cfg(feature = "kv")]
+    for (key, value) in crate::kv::collect(record) {
+        write_field(f, &field_name(&key), value)?;
+    }
+    Ok(())
+}
+
+/// Writes a field, as `NAME=value`, or in the binary form when the value
+/// contains a newline
+fn write_field(out: &mut impl Write, name: &str, value: impl std::fmt::Display) -> io::Result<()> {
+    let value = value.to_string();
+    if value.contains('\n') {
+        writeln!(out, "{name}")?;
+        out.write_all(&(value.len() as u64).to_le_bytes())?;
+        out.write_all(value.as_bytes())?;
+        writeln!(out)
+    } else {
+        writeln!(out, "{name}={value}")
+    }
+}
+
+/// Returns a valid field name from a key: uppercase letters, digits and `_`,
+/// not starting with `_` or a digit, and up to 64 characters
+#[cfg(feature = "kv")]
+fn field_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| match c {
+            'a'..='z' => c.to_ascii_uppercase(),
+            'A'..='Z' | '0'..='9' => c,
+            _ => '_',
+        })
+        .collect();
+    let name = name.trim_start_matches('_');
+    let mut name = if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("KV_{name}")
+    } else {
+        name.to_owned()
+    };
+    name.truncate(64);
+    name
+}
+
+/// Socket of systemd-journald, receiving one log per datagram
+pub(crate) struct JournaldSocket(UnixDatagram);
+
+impl JournaldSocket {
+    /// Sends a log too large for a datagram, through a sealed memory file
+    fn send_memfd(&self, buf: &[u8]) -> io::Result<()> {
+        // SAFETY: the name is a valid C string, and the returned file
+        // descriptor is owned by the `File`
+        let mut file = unsafe {
+            let fd = libc::memfd_create(
+                c"lovely_env_logger".as_ptr(),
+                libc::MFD_ALLOW_SEALING | libc::MFD_CLOEXEC,
+            );
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            File::from_raw_fd(fd)
+        };
+        file.write_all(buf)?;
+        let seals =
+            libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE | libc::F_SEAL_SEAL;
+        // SAFETY: the file descriptor is valid for the lifetime of `file`
+        if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_ADD_SEALS, seals) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        self.send_fd(file.as_raw_fd())
+    }
+
+    /// Sends a file descriptor, with an empty datagram
+    fn send_fd(&self, fd: libc::c_int) -> io::Result<()> {
+        const FD_SIZE: libc::c_uint = std::mem::size_of::<libc::c_int>() as libc::c_uint;
+        // SAFETY: `CMSG_SPACE` only computes a size
+        let mut control = vec![0u8; unsafe { libc::CMSG_SPACE(FD_SIZE) } as usize];
+        // SAFETY: the message only points to `control`, which is large enough
+        // for a single control message holding a file descriptor
+        unsafe {
+            let mut msg: libc::msghdr = std::mem::zeroed();
+            msg.msg_control = control.as_mut_ptr().cast();
+            msg.msg_controllen = control.len() as _;
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_SOCKET;
+            (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(FD_SIZE) as _;
+            std::ptr::write_unaligned(libc::CMSG_DATA(cmsg).cast::<libc::c_int>(), fd);
+            if libc::sendmsg(self.0.as_raw_fd(), &msg, 0) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for JournaldSocket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.0.send(buf) {
+            Ok(_) => {}
+            Err(err)
+                if err.raw_os_error() == Some(libc::EMSGSIZE)
+                    || err.raw_os_error() == Some(libc::ENOBUFS) =>
+            {
+                self.send_memfd(buf)?
+            }
+            Err(err) => return Err(err),
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Write;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::fs::FileExt;
+    use std::os::unix::net::UnixDatagram;
+
+    use super::{Journald, JournaldMode};
+
+    /// Receives a file descriptor sent with an empty datagram
+    fn receive_fd(socket: &UnixDatagram) -> OwnedFd {
+        const FD_SIZE: libc::c_uint = std::mem::size_of::<libc::c_int>() as libc::c_uint;
+        // SAFETY: `CMSG_SPACE` only computes a size
+        let mut control = vec![0u8; unsafe { libc::CMSG_SPACE(FD_SIZE) } as usize];
+        let mut data = [0u8; 1];
+        // SAFETY: the message only points to `data` and `control`, and the
+        // received file descriptor is owned by the returned `OwnedFd`
+        unsafe {
+            let mut iov = libc::iovec {
+                iov_base: data.as_mut_ptr().cast(),
+                iov_len: data.len(),
+            };
+            let mut msg: libc::msghdr = std::mem::zeroed();
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr().cast();
+            msg.msg_controllen = control.len() as _;
+            let len = libc::recvmsg(socket.as_raw_fd(), &mut msg, libc::MSG_CMSG_CLOEXEC);
+            assert_eq!(len, 0, "{}", std::io::Error::last_os_error());
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            assert!(!cmsg.is_null());
+            assert_eq!((*cmsg).cmsg_level, libc::SOL_SOCKET);
+            assert_eq!((*cmsg).cmsg_type, libc::SCM_RIGHTS);
+            let fd = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg).cast::<libc::c_int>());
+            OwnedFd::from_raw_fd(fd)
+        }
+    }
+
+    #[test]
+    fn large_logs_are_sent_through_sealed_memfds() {
+        let path = std::env::temp_dir().join(format!("lovely-memfd-{}", std::process::id()));
+        let socket = UnixDatagram::bind(&path).unwrap();
+        let journald = Journald {
+            mode: JournaldMode::Always,
+            socket: path.clone(),
+        };
+        let mut sender = journald.connect(true).unwrap();
+        let log = vec![b'x'; 1 << 20];
+        assert_eq!(sender.write(&log).unwrap(), log.len());
+
+        let file = File::from(receive_fd(&socket));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(file.metadata().unwrap().len(), log.len() as u64);
+        // SAFETY: the file descriptor is valid for the lifetime of `file`
+        let seals = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GET_SEALS) };
+        assert_eq!(
+            seals,
+            libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE | libc::F_SEAL_SEAL
+        );
+        // The offset is shared with the sender, which left it at the end
+        let mut received = vec![0; log.len()];
+        file.read_exact_at(&mut received, 0).unwrap();
+        assert!(received == log);
+    }
+}
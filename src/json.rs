@@ -0,0 +1,158 @@
+//! JSON Lines output.
+
+use std::fmt::Display;
+use std::io::{self, Write};
+
+use env_logger::fmt::Formatter;
+
+use crate::{thread_id, Config};
+
+/// Writes a record as a JSON object, on a single line
+pub(crate) fn write(f: &mut Formatter, record: &log::Record, config: &Config) -> io::Result<()> {
+    write!(f, "{{")?;
+    #[cfg(feature = "humantime")]
+    {
+        let timestamp = f.timestamp_millis();
+        write!(f, "\"timestamp\":")?;
+        write_str(f, timestamp)?;
+        write!(f, ",")?;
+    }
+    write!(f, "\"level\":")?;
+    write_str(f, record.level())?;
+    write!(f, ",\"target\":")?;
+    write_str(f, record.target())?;
+    if let Some(file) = record.file() {
+        write!(f, ",\"file\":")?;
+        write_str(f, file)?;
+    }
+    if let Some(line) = record.line() {
+        write!(f, ",\"line\":{line}")?;
+    }
+    if config.with_thread_name || config.with_thread_id {
+        let current = std::thread::current();
+        if config.with_thread_name {
+            write!(f, ",\"thread\":")?;
+            write_str(f, current.name().unwrap_or("<unnamed>"))?;
+        }
+        if config.with_thread_id {
+            write!(f, ",\"thread_id\":{}", thread_id(&current))?;
+        }
+    }
+    write!(f, ",\"message\":")?;
+    write_str(f, record.args())?;
+    #[cfg(feature = "kv")]
+    write_kv(f, record)?;
+    writeln!(f, "}}")
+}
+
+/// Writes the key-values of a record as fields of the JSON object, keeping
+/// booleans and numbers as such
+#[cfg(feature = "kv")]
+fn write_kv(out: &mut impl Write, record: &log::Record) -> io::Result<()> {
+    use log::kv::{Error, Key, Value, VisitSource};
+
+    struct Visitor<'a, W>(&'a mut W);
+
+    impl<'kvs, W: Write> VisitSource<'kvs> for Visitor<'_, W> {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            write!(self.0, ",")?;
+            write_str(self.0, key)?;
+            write!(self.0, ":")?;
+            if let Some(v) = value.to_bool() {
+                write!(self.0, "{v}")?;
+            } else if let Some(v) = value.to_i64() {
+                write!(self.0, "{v}")?;
+            } else if let Some(v) = value.to_u64() {
+                write!(self.0, "{v}")?;
+            } else if let Some(v) = value.to_f64().filter(|v| v.is_finite()) {
+                write!(self.0, "{v}")?;
+            } else {
+                write_str(self.0, value)?;
+            }
+            Ok(())
+        }
+    }
+
+    record
+        .key_values()
+        .visit(&mut Visitor(out))
+        .map_err(io::Error::other)
+}
+
+/// Writes a value as a JSON string, escaping it
+pub(crate) fn write_str(out: &mut impl Write, value: impl Display) -> io::Result<()> {
+    let value = value.to_string();
+    write!(out, "\"")?;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        let escaped = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            c if c.is_control() => {
+                out.write_all(&value.as_bytes()[start..i])?;
+                write!(out, "\\u{:04x}", c as u32)?;
+                start = i + c.len_utf8();
+                continue;
+            }
+            _ => continue,
+        };
+        out.write_all(&value.as_bytes()[start..i])?;
+        out.write_all(escaped.as_bytes())?;
+        start = i + 1;
+    }
+    out.write_all(&value.as_bytes()[start..])?;
+    write!(out, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_str;
+
+    /// Returns a value written as a JSON string
+    fn string(value: &str) -> String {
+        let mut out = Vec::new();
+        write_str(&mut out, value).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn strings_are_escaped() {
+        assert_eq!(string("hello"), r#""hello""#);
+        assert_eq!(string(""), r#""""#);
+        assert_eq!(string(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(string(r"C:\temp"), r#""C:\\temp""#);
+        assert_eq!(string("a\nb\r\tc"), r#""a\nb\r\tc""#);
+        assert_eq!(string("\u{1}"), r#""\u0001""#);
+        assert_eq!(string("\u{7f}é"), "\"\\u007fé\"");
+    }
+
+    #[test]
+    #[cfg(feature = "kv")]
+    fn kv_types() {
+        use log::kv::Value;
+
+        let kvs: [(&str, Value); 8] = [
+            ("ok", true.into()),
+            ("count", (-3i64).into()),
+            ("big", u64::MAX.into()),
+            ("ratio", 0.5.into()),
+            ("nan", f64::NAN.into()),
+            ("inf", f64::INFINITY.into()),
+            ("name", "a\"b".into()),
+            ("id", "42".into()),
+        ];
+        let record = log::Record::builder().key_values(&kvs).build();
+        let mut out = Vec::new();
+        super::write_kv(&mut out, &record).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                r#","ok":true,"count":-3,"big":18446744073709551615,"ratio":0.5,"#,
+                r#""nan":"NaN","inf":"inf","name":"a\"b","id":"42""#,
+            )
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Display of the key-values of the log records, as set through
+//! `Config::kv` or the `RUST_LOG_KV` environment variable.
+
+use std::io::{self, Write};
+
+use log::kv::{Error, Key, Value, VisitSource};
+
+use crate::{Styled, Theme};
+
+/// How the key-values of the log records are displayed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KvDisplay {
+    /// After the message, on the same line
+    #[default]
+    Inline,
+    /// After the message, each on its own line, indented under the message
+    Block,
+    /// Not displayed
+    Hidden,
+}
+
+impl KvDisplay {
+    /// Parses `inline`, `block` or `hidden`
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "inline" => Some(Self::Inline),
+            "block" => Some(Self::Block),
+            "hidden" => Some(Self::Hidden),
+            _ => None,
+        }
+    }
+
+    /// Writes the key-values of a record, as `key=value`.
+    ///
+    /// In block mode, each key-value is written on its own line, indented
+    /// after the given column.
+    pub(crate) fn write(
+        self,
+        out: &mut impl Write,
+        record: &log::Record,
+        theme: &Theme,
+        column: usize,
+    ) -> io::Result<()> {
+        if self == Self::Hidden {
+            return Ok(());
+        }
+        for (key, value) in collect(record) {
+            match self {
+                Self::Block => write!(out, "\n{:width$}", "", width = column + 2)?,
+                _ => write!(out, " ")?,
+            }
+            let key = Styled {
+                style: theme.kv_key,
+                value: key,
+            };
+            let value = Styled {
+                style: theme.kv_value,
+                value: Quoted(&value),
+            };
+            write!(out, "{key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the key-values of a record, in order
+pub(crate) fn collect(record: &log::Record) -> Vec<(String, String)> {
+    struct Collect(Vec<(String, String)>);
+
+    impl<'kvs> VisitSource<'kvs> for Collect {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            self.0.push((key.to_string(), value.to_string()));
+            Ok(())
+        }
+    }
+
+    let mut visitor = Collect(Vec::new());
+    // Collecting into a vector never fails
+    let _ = record.key_values().visit(&mut visitor);
+    visitor.0
+}
+
+/// Struct to display a value, quoted when it is empty or contains spaces,
+/// `=` or `"`
+pub(crate) struct Quoted<'a>(pub(crate) &'a str);
+
+impl std::fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.0.is_empty()
+            || self
+                .0
+                .chars()
+                .any(|c| c.is_whitespace() || c == '=' || c == '"')
+        {
+            write!(f, "{:?}", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::kv::Value;
+
+    use super::{KvDisplay, Quoted};
+    use crate::tests::Capture;
+    use crate::{Config, PaddingStrategy};
+
+    /// Returns the line logged with two key-values
+    fn render(config: Config) -> String {
+        let capture = Capture::new(config);
+        capture.log_kv("hello", &[("user", "ada".into()), ("id", 42.into())]);
+        capture.output()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(KvDisplay::parse("inline"), Some(KvDisplay::Inline));
+        assert_eq!(KvDisplay::parse("block"), Some(KvDisplay::Block));
+        assert_eq!(KvDisplay::parse("hidden"), Some(KvDisplay::Hidden));
+        assert_eq!(KvDisplay::parse("Block"), None);
+        assert_eq!(KvDisplay::parse(""), None);
+    }
+
+    #[test]
+    fn inline() {
+        let line = render(Config::default());
+        assert_eq!(line, "INFO  app hello user=ada id=42\n");
+    }
+
+    #[test]
+    fn block_is_indented_under_the_message() {
+        let line = render(Config {
+            kv: KvDisplay::Block,
+            ..Config::default()
+        });
+        assert_eq!(
+            line,
+            "INFO  app hello\n            user=ada\n            id=42\n"
+        );
+
+        let line = render(Config {
+            kv: KvDisplay::Block,
+            with_padding: true,
+            padding: PaddingStrategy::Fixed(8),
+            ..Config::default()
+        });
+        assert_eq!(
+            line,
+            "INFO  app      > hello\n                   user=ada\n                   id=42\n"
+        );
+    }
+
+    #[test]
+    fn hidden() {
+        let line = render(Config {
+            kv: KvDisplay::Hidden,
+            ..Config::default()
+        });
+        assert_eq!(line, "INFO  app hello\n");
+    }
+
+    #[test]
+    fn values_are_quoted_when_needed() {
+        assert_eq!(Quoted("ada").to_string(), "ada");
+        assert_eq!(Quoted("").to_string(), r#""""#);
+        assert_eq!(Quoted("Ada Lovelace").to_string(), r#""Ada Lovelace""#);
+        assert_eq!(Quoted("a=b").to_string(), r#""a=b""#);
+        assert_eq!(Quoted(r#"say "hi""#).to_string(), r#""say \"hi\"""#);
+        assert_eq!(Quoted("tab\there").to_string(), r#""tab\there""#);
+
+        let capture = Capture::new(Config::default());
+        capture.log_kv("hello", &[("name", Value::from("Ada Lovelace"))]);
+        assert_eq!(capture.output(), "INFO  app hello name=\"Ada Lovelace\"\n");
+    }
+}
@@ -0,0 +1,110 @@
+//! Display of the levels, as set through `Config::level_style` or the
+//! `RUST_LOG_LEVEL_STYLE` and `RUST_LOG_SHORT_LEVELS` environment variables.
+
+use log::Level;
+
+/// How the levels are displayed.
+///
+/// Labels are padded to the width of the widest label of the style, so that
+/// the columns after them stay aligned.
+///
+/// ```
+/// use log::Level;
+/// use lovely_env_logger::LevelStyle;
+///
+/// assert_eq!(LevelStyle::Short.label(Level::Warn), "WRN");
+/// assert_eq!(LevelStyle::Icons.label(Level::Error), "✖");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum LevelStyle {
+    /// `ERROR`, `WARN`, `INFO`, `DEBUG` and `TRACE`
+    #[default]
+    Long,
+    /// `ERR`, `WRN`, `INF`, `DBG` and `TRC`
+    Short,
+    /// `E`, `W`, `I`, `D` and `T`
+    Letter,
+    /// `✖`, `⚠`, `ℹ`, `⚙` and `…`
+    Icons,
+    /// Custom labels of the error, warn, info, debug and trace levels, in
+    /// this order
+    Custom([String; 5]),
+}
+
+impl LevelStyle {
+    /// Parses `long`, `short`, `letter`, `icons` or
+    /// `custom:<error>,<warn>,<info>,<debug>,<trace>`
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "long" => Some(Self::Long),
+            "short" => Some(Self::Short),
+            "letter" => Some(Self::Letter),
+            "icons" => Some(Self::Icons),
+            _ => {
+                let mut labels = value.strip_prefix("custom:")?.split(',');
+                let custom = [(); 5].map(|()| labels.next().map(str::to_owned));
+                match (custom, labels.next()) {
+                    ([Some(error), Some(warn), Some(info), Some(debug), Some(trace)], None) => {
+                        Some(Self::Custom([error, warn, info, debug, trace]))
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Returns the label of a level, not padded
+    pub fn label(&self, level: Level) -> &str {
+        let labels = match self {
+            Self::Long => ["ERROR", "WARN", "INFO", "DEBUG", "TRACE"],
+            Self::Short => ["ERR", "WRN", "INF", "DBG", "TRC"],
+            Self::Letter => ["E", "W", "I", "D", "T"],
+            Self::Icons => ["✖", "⚠", "ℹ", "⚙", "…"],
+            Self::Custom(labels) => return &labels[level as usize - 1],
+        };
+        labels[level as usize - 1]
+    }
+
+    /// Returns the width of the widest label, in characters
+    pub(crate) fn width(&self) -> usize {
+        Level::iter()
+            .map(|level| self.label(level).chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::Level;
+
+    use super::LevelStyle;
+    use crate::tests::Capture;
+    use crate::Config;
+
+    /// Returns the lines logged at the warn and info levels
+    fn render(level_style: LevelStyle) -> String {
+        let capture = Capture::new(Config {
+            level_style,
+            ..Config::default()
+        });
+        capture.log(Level::Warn, "hello");
+        capture.log(Level::Info, "hello");
+        capture.output()
+    }
+
+    #[test]
+    fn labels_are_padded() {
+        assert_eq!(
+            render(LevelStyle::Long),
+            "WARN  app hello\nINFO  app hello\n"
+        );
+        assert_eq!(render(LevelStyle::Short), "WRN app hello\nINF app hello\n");
+        assert_eq!(render(LevelStyle::Letter), "W app hello\nI app hello\n");
+        let labels = ["Fehler", "Warnung", "Info", "Debug", "Spur"].map(str::to_owned);
+        assert_eq!(
+            render(LevelStyle::Custom(labels)),
+            "Warnung app hello\nInfo    app hello\n"
+        );
+    }
+}
@@ -34,6 +34,20 @@
 //! This crate uses [env_logger][] internally, so the same ways of enabling
 //! logs through an environment variable are supported.
 //!
+//! ## Layout
+//!
+//! All the options can be combined. Unless a template is set through
+//! `Config::format`, the columns of a log line are always in this order:
+//! 1. the system timestamp, when enabled
+//! 2. the uptime timestamp, when enabled
+//! 3. the relative timestamp, when enabled
+//! 4. the level
+//! 5. the thread name and id, when enabled
+//! 6. the target, followed by the file name and line number when enabled
+//! 7. the `>` separator, when padding is enabled
+//! 8. the message
+//! 9. the key-values of the log, such as `user_id=42`
+//!
 //! ## Configuration through environment variables
 //! Some options can be set through environment variables that have priority
 //! over configuration set through the `Config` structure.
@@ -42,9 +56,20 @@
 //! adding a suffix added to `RUST_LOG` or the environment variable used to
 //! filter the traces.
 //!
+//! ### `RUST_LOG_LEVEL_STYLE`
+//! How the levels are displayed:
+//! - `long`: `ERROR`, `WARN`, `INFO`, `DEBUG` and `TRACE`. This is the default.
+//! - `short`: `ERR`, `WRN`, `INF`, `DBG` and `TRC`
+//! - `letter`: `E`, `W`, `I`, `D` and `T`
+//! - `icons`: `✖`, `⚠`, `ℹ`, `⚙` and `…`
+//! - `custom:<error>,<warn>,<info>,<debug>,<trace>`: custom labels, such as
+//!   `custom:Fehler,Warnung,Info,Debug,Spur`
+//!
+//! Labels are padded to the width of the widest one.
+//!
 //! ### `RUST_LOG_SHORT_LEVELS`
 //! Display levels on 3 characters to `1`. Display them as 5 characters
-//! otherwise.
+//! otherwise. `RUST_LOG_LEVEL_STYLE` has priority over it.
 //!
 //! ### `RUST_LOG_WITH_FILE_NAME`
 //! Display the file calling the log macro when set to `1`. Disable it otherwise.
@@ -52,18 +77,231 @@
 //! ### `RUST_LOG_WITH_LINE_NUMBER`
 //! Display the line number calling the log macro when set to `1`. Disable it otherwise.
 //!
+//! ### `RUST_LOG_HYPERLINKS`
+//! When set to `1`, make the file names and line numbers clickable links to
+//! the source files, with OSC 8 escape sequences. Never do it when set to `0`.
+//! When set to `auto`, which is the default, do it only when standard error is
+//! a terminal known to support them.
+//!
+//! ### `RUST_LOG_EDITOR_URL`
+//! Template of the URL of the links, such as `vscode://file/{path}:{line}`,
+//! where `{path}` is the absolute path of the file and `{line}` the line
+//! number. The links are `file://` URLs otherwise.
+//!
+//! ### `RUST_LOG_WITH_THREAD_NAME`
+//! Display the name of the thread calling the log macro when set to `1`. Disable it otherwise.
+//!
+//! ### `RUST_LOG_WITH_THREAD_ID`
+//! Display the id of the thread calling the log macro when set to `1`. Disable it otherwise.
+//!
 //! ### `RUST_LOG_WITH_PADDING`
 //! Display the log lines with padding after module name, when set to `1`. Disable it otherwise.
 //!
+//! ### `RUST_LOG_PADDING`
+//! How the width of the padded module names is computed, when padding is
+//! enabled:
+//! - `fixed:<width>`: pad to a fixed width, up to 1024
+//! - `grow`: pad to the width of the longest module name seen so far. This is
+//!   the default.
+//! - `rolling:<lines>`: pad to the width of the longest module name of the
+//!   last given number of lines
+//! - `filters`: pad to the width of the longest module name named in the
+//!   filter directives, then grow
+//!
 //! ### `RUST_LOG_WITH_SYSTEM_TIMESTAMPS`
 //! Enable timestamps when set to `1`. Disable it otherwise.
 //! Requires to be compiled with the `humantime` feature.
 //!
+//! ### `RUST_LOG_TIME_FORMAT`
+//! strftime-like format of the system timestamps and of the dates displayed by
+//! relative timestamps, such as `%Y-%m-%d %H:%M:%S%.f`. `%.f` is replaced by
+//! the fraction of second matching `RUST_LOG_TIME_PRECISION`. System
+//! timestamps are formatted as RFC 3339 otherwise.
+//! Requires to be compiled with the `humantime` or `reltime` feature.
+//!
+//! ### `RUST_LOG_TIME_PRECISION`
+//! Precision of the system timestamps and of the dates displayed by relative
+//! timestamps: `s`, `ms`, `us` or `ns`. System timestamps have milliseconds,
+//! and dates have seconds otherwise.
+//!
+//! ### `RUST_LOG_TIMEZONE`
+//! Time zone of the system timestamps and of the dates displayed by relative
+//! timestamps: `utc`, `local`, or a fixed offset such as `+02:00`. System
+//! timestamps are in UTC, and dates in local time otherwise.
+//! Requires to be compiled with the `humantime` or `reltime` feature.
+//!
+//! ### `RUST_LOG_WITH_UPTIME_TIMESTAMPS`
+//! When set to `1`, display timestamps using the time elapsed since the logger
+//! was built, or since the last call to `reset_uptime()`, as `[   12.345678]`.
+//! Disable it otherwise.
+//!
 //! ### `RUST_LOG_WITH_RELATIVE_TIMESTAMPS`
 //! When set to `1`, display timestamps using the difference compared to the
 //! previous log, or the date of log if the difference is too large.
 //! Requires to be compiled with the `reltime` feature.
 //!
+//! ### `RUST_LOG_RELTIME_THRESHOLD`
+//! Largest difference displayed by relative timestamps, in seconds, such as
+//! `10` or `2.5`. The date of the log is displayed instead when the difference
+//! is larger. Defaults to 10 seconds.
+//!
+//! ### `RUST_LOG_RELTIME_STYLE`
+//! How the differences displayed by relative timestamps are formatted:
+//! - `dmesg`: seconds with nanoseconds, as `[  +1.234567890]`. This is the
+//!   default.
+//! - `human`: the most readable unit, as `[+123µs]`, `[+4.2ms]` or
+//!   `[ +1.7s]`, with 2 significant digits
+//! - `human:<precision>`: same as `human`, with the given number of
+//!   significant digits, up to 9
+//!
+//! ### `RUST_LOG_RELTIME_ANCHOR_LINES`
+//! Display the date of the log instead of the difference at least every given
+//! number of logs, such as `100`, and for the first log of each day.
+//! Requires to be compiled with the `reltime` feature.
+//!
+//! ### `RUST_LOG_RELTIME_ANCHOR_INTERVAL`
+//! Display the date of the log instead of the difference when the given
+//! number of seconds, such as `60` or `0.5`, has elapsed since the last date
+//! displayed, and for the first log of each day.
+//! Requires to be compiled with the `reltime` feature.
+//!
+//! ### `RUST_LOG_RELTIME_DAY_BANNER`
+//! When set to `1`, display the date of the first log of each day, after a
+//! line such as `──── Fri 2026-10-16 ────`. Disable it otherwise.
+//! Requires to be compiled with the `reltime` feature.
+//!
+//! ### `RUST_LOG_RELTIME_HEAT`
+//! When set to `1`, color the relative timestamps by the time elapsed since
+//! the previous log, like the `INFO` level from 10ms, the `WARN` level from
+//! 100ms and the `ERROR` level from 1s. Disable it otherwise.
+//! Requires to be compiled with the `reltime` feature.
+//!
+//! ### `RUST_LOG_GAP_MARKER`
+//! Print a line such as `──── 2.3s gap ────` before the logs following a
+//! pause longer than the given number of seconds, such as `1` or `0.5`.
+//! Requires to be compiled with the `reltime` feature.
+//!
+//! ### `RUST_LOG_THEME`
+//! Use one of the built-in color themes: `default`, `solarized` or
+//! `monochrome`.
+//!
+//! ### `RUST_LOG_COLORS`
+//! Override the styles of the theme, in a way similar to `LS_COLORS`, such as
+//! `error=1;31:target=36`. See `Theme::apply_colors` for the available keys.
+//!
+//! ### `RUST_LOG_COLOR_TARGETS`
+//! Give each target its own color, derived from a hash of its name, when set
+//! to `1` or `target`. Give each crate its own color when set to `crate`.
+//! Use the target style of the theme for every target when set to `0` or
+//! `off`.
+//!
+//! ### `RUST_LOG_HIGHLIGHT_VALUES`
+//! Highlight the numbers, durations, quoted strings, file paths, IP addresses
+//! and URLs found in the messages when set to `1`, with the styles of the
+//! theme. Disable it otherwise. Nothing is highlighted when colors are
+//! disabled.
+//!
+//! ### `RUST_LOG_HIGHLIGHT`
+//! Highlight the parts of the messages matching regular expressions, with
+//! rules such as `timeout|refused=red,bold;user_id=\d+=cyan`. Rules are
+//! separated by `;`, and the style of each rule follows its last `=`. Styles
+//! are made of colors such as `red`, `bright_red`, `on_blue` or `208`, and of
+//! effects such as `bold`, `dim`, `italic` or `underline`, separated by `,`.
+//! Requires to be compiled with the `regex` feature.
+//!
+//! ### `RUST_LOG_HIGHLIGHT_DIM`
+//! Dim the lines whose message does not match any of the `RUST_LOG_HIGHLIGHT`
+//! rules when set to `1`. Disable it otherwise.
+//!
+//! ### `RUST_LOG_MULTILINE`
+//! How messages spanning multiple lines are displayed:
+//! - `indent`: align continuation lines under the first line of the message
+//! - `indent:<gutter>`: same as `indent`, with a gutter character such as `│`
+//!   before continuation lines
+//! - `repeat-header`: repeat the timestamps, level and target on every line
+//!
+//! Continuation lines start at the first column otherwise.
+//!
+//! ### `RUST_LOG_WRAP`
+//! When set to `1`, wrap the messages longer than the width of the terminal at
+//! word boundaries, and align the wrapped parts under the first line of the
+//! message. The width is read from `COLUMNS`, or from the terminal. Messages
+//! are never wrapped when standard error is not a terminal.
+//! Requires to be compiled with the `wrap` feature.
+//!
+//! ### `RUST_LOG_TARGET_SHORTENING`
+//! How targets longer than `RUST_LOG_MAX_TARGET_WIDTH` are shortened:
+//! - `truncate`: cut the end of the target
+//! - `abbreviate`: abbreviate the leading segments of the target, as
+//!   `m::s::b::invoices::renderer`. Every leading segment is abbreviated when
+//!   no maximum width is set.
+//! - `elide`: replace the middle of the target by an ellipsis
+//!
+//! ### `RUST_LOG_MAX_TARGET_WIDTH`
+//! Maximum width of the targets, in characters. Padding is computed on the
+//! shortened targets.
+//!
+//! ### `RUST_LOG_KV`
+//! How the key-values of the log records are displayed:
+//! - `inline`: after the message, on the same line. This is the default.
+//! - `block`: after the message, each on its own line
+//! - `hidden`: not displayed
+//!
+//! Requires to be compiled with the `kv` feature.
+//!
+//! ### `RUST_LOG_OUTPUT`
+//! Format of the log lines:
+//! - `pretty`: lovely colored lines. This is the default.
+//! - `json`: one JSON object per line, with the `timestamp`, `level`,
+//!   `target`, `file`, `line` and `message` fields, the `thread` and
+//!   `thread_id` fields when enabled, and the key-values of the log. The
+//!   timestamp requires to be compiled with the `humantime` feature.
+//! - `logfmt`: one logfmt line per log, such as
+//!   `ts=2024-01-31T12:34:56.789Z level=info target=app msg="hello world"`,
+//!   with the same fields as `json`.
+//! - `syslog`: syslog messages, with a priority computed from the level and
+//!   the facility. See the `RUST_LOG_SYSLOG_*` variables below.
+//!
+//! ### `RUST_LOG_SYSLOG_FORMAT`
+//! Format of the syslog messages, when `RUST_LOG_OUTPUT` is `syslog`: `rfc5424`
+//! or `rfc3164`.
+//!
+//! ### `RUST_LOG_SYSLOG_FACILITY`
+//! Facility of the syslog messages, such as `user`, `daemon` or `local0`.
+//!
+//! ### `RUST_LOG_SYSLOG_APP_NAME`
+//! Name of the application in the syslog messages. The name of the executable
+//! is used otherwise.
+//!
+//! ### `RUST_LOG_SYSLOG_HOSTNAME`
+//! Name of the host in the syslog messages.
+//!
+//! ### `RUST_LOG_SYSLOG_SOCKET`
+//! Path of a Unix datagram socket to send the syslog messages to, such as
+//! `/dev/log`. The syslog messages are written to standard error otherwise.
+//!
+//! ### `RUST_LOG_JOURNALD`
+//! When set to `1`, send the logs to systemd-journald with its native
+//! protocol, as structured fields. Never do it when set to `0`. When set to
+//! `auto`, which is the default, do it only when standard error is connected
+//! to the journal and `RUST_LOG_OUTPUT` is `pretty`.
+//! Requires to be compiled with the `journald` feature, on Linux.
+//!
+//! ### `RUST_LOG_JOURNALD_SOCKET`
+//! Path of the socket of systemd-journald, `/run/systemd/journal/socket` by
+//! default.
+//!
+//! ### `RUST_LOG_SD_PREFIX`
+//! When set to `1`, prefix every line with its sd-daemon priority, such as
+//! `<3>` for errors, and disable colors, so that the systemd journal can
+//! filter the logs by priority. Never do it when set to `0`. When set to
+//! `auto`, which is the default, do it only when standard error is connected
+//! to the journal and the native journald protocol is not used.
+//!
+//! ### `RUST_LOG_FORMAT`
+//! Template of the log lines, such as `{time} {level} {target}{location} > {msg}`.
+//! See `Config::format` for the available placeholders.
+//!
 //! [env_logger]: https://docs.rs/env_logger
 
 #[doc(hidden)]
@@ -71,26 +309,92 @@ pub extern crate env_logger;
 
 extern crate log;
 
-#[cfg(feature = "reltime")]
-use chrono::{DateTime, Local, Timelike};
 #[cfg(feature = "reltime")]
 use std::sync::{Arc, Mutex};
+#[cfg(feature = "reltime")]
+use std::time::{Duration, Instant};
 
+use std::borrow::Cow;
 use std::default::Default;
 use std::env;
 use std::fmt;
-use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "wrap")]
+use std::io::IsTerminal;
+use std::thread;
 
-use env_logger::{
-    fmt::style::{AnsiColor, Style},
-    Builder,
-};
+use env_logger::{fmt::style::Style, Builder};
 use log::Level;
 
+mod highlight;
+mod hyperlink;
+#[cfg(all(feature = "journald", target_os = "linux"))]
+mod journald;
+mod json;
+#[cfg(feature = "kv")]
+mod kv;
+mod level;
+mod logfmt;
+mod multiline;
+mod output;
+mod padding;
+#[cfg(feature = "reltime")]
+mod reltime;
+mod shorten;
+mod syslog;
+mod systemd;
+mod template;
+mod theme;
+#[cfg(any(feature = "humantime", feature = "reltime"))]
+mod timestamp;
+mod uptime;
+mod wrap;
+
+#[cfg(feature = "regex")]
+pub use highlight::HighlightRule;
+use highlight::Highlighted;
+use hyperlink::{Hyperlink, Hyperlinks};
+#[cfg(all(feature = "journald", target_os = "linux"))]
+pub use journald::{Journald, JournaldMode};
+#[cfg(feature = "kv")]
+pub use kv::KvDisplay;
+pub use level::LevelStyle;
+#[cfg(feature = "kv")]
+use multiline::visible_width;
+pub use multiline::Multiline;
+pub use output::Output;
+use padding::Padding;
+pub use padding::PaddingStrategy;
+#[cfg(feature = "reltime")]
+pub use reltime::RelTimeStyle;
+pub use shorten::TargetShortening;
+pub use syslog::{Facility, Syslog, SyslogFormat};
+use template::{Field, Template};
+pub use theme::{TargetColors, Theme};
+#[cfg(any(feature = "humantime", feature = "reltime"))]
+use timestamp::TimeFormatter;
+#[cfg(any(feature = "humantime", feature = "reltime"))]
+pub use timestamp::TimePrecision;
+#[cfg(any(feature = "humantime", feature = "reltime"))]
+pub use timestamp::Timezone;
+pub use uptime::reset_uptime;
+
 /// Default environment variable to filter logs
 const RUST_LOG_ENV: &str = "RUST_LOG";
 
 /// Configuration for the lovely env logger
+///
+/// Every option can be combined with the others:
+///
+/// ```
+/// use lovely_env_logger::Config;
+///
+/// lovely_env_logger::init(Config {
+///     with_padding: true,
+///     with_file_name: true,
+///     with_line_number: true,
+///     ..Config::default()
+/// });
+/// ```
 pub struct Config {
     #[cfg(feature = "humantime")]
     /// Whether to display a timestamp
@@ -98,15 +402,159 @@ pub struct Config {
     #[cfg(feature = "reltime")]
     /// Whether to display a timestamp as reltime
     pub reltime: bool,
+    #[cfg(feature = "reltime")]
+    /// Largest difference displayed by relative timestamps. The date of the
+    /// log is displayed instead when the difference is larger.
+    ///
+    /// Differences are measured with a monotonic clock, so they are not
+    /// affected by changes of the system clock.
+    pub reltime_threshold: Duration,
+    #[cfg(feature = "reltime")]
+    /// How the differences displayed by relative timestamps are formatted
+    pub reltime_style: RelTimeStyle,
+    #[cfg(feature = "reltime")]
+    /// Display the date of the log instead of the difference at least every
+    /// given number of logs. The first log, and the first log of each day,
+    /// then always display the date.
+    pub reltime_anchor_lines: Option<usize>,
+    #[cfg(feature = "reltime")]
+    /// Display the date of the log instead of the difference when this
+    /// duration has elapsed since the last date displayed, and for the first
+    /// log of each day
+    pub reltime_anchor_interval: Option<Duration>,
+    #[cfg(feature = "reltime")]
+    /// Display the date of the first log of each day, after a line such as
+    /// `──── Fri 2026-10-16 ────`
+    pub reltime_day_banner: bool,
+    #[cfg(feature = "reltime")]
+    /// Color the relative timestamps by the time elapsed since the previous
+    /// log, like the info level from 10ms, the warn level from 100ms and the
+    /// error level from 1s
+    pub reltime_heat: bool,
+    #[cfg(feature = "reltime")]
+    /// Print a line such as `──── 2.3s gap ────` before the logs following a
+    /// pause longer than this duration, whether timestamps are displayed or
+    /// not
+    pub gap_marker: Option<Duration>,
+    #[cfg(any(feature = "humantime", feature = "reltime"))]
+    /// strftime-like format of the system timestamps and of the dates
+    /// displayed by relative timestamps, such as `%Y-%m-%d %H:%M:%S%.f`.
+    /// `%.f` is replaced by the fraction of second matching `time_precision`.
+    /// When `None`, system timestamps are formatted as RFC 3339.
+    ///
+    /// ```
+    /// use lovely_env_logger::{Config, TimePrecision, Timezone};
+    ///
+    /// let config = Config {
+    ///     time_format: Some("%Y-%m-%d %H:%M:%S%.f".to_owned()),
+    ///     time_precision: Some(TimePrecision::Millis),
+    ///     timezone: Some(Timezone::Utc),
+    ///     ..Config::default()
+    /// };
+    /// ```
+    pub time_format: Option<String>,
+    #[cfg(any(feature = "humantime", feature = "reltime"))]
+    /// Precision of the system timestamps and of the dates displayed by
+    /// relative timestamps. When `None`, system timestamps have
+    /// milliseconds, and dates have seconds.
+    pub time_precision: Option<TimePrecision>,
+    #[cfg(any(feature = "humantime", feature = "reltime"))]
+    /// Time zone of the system timestamps and of the dates displayed by
+    /// relative timestamps. When `None`, system timestamps are in UTC, and
+    /// dates in local time.
+    pub timezone: Option<Timezone>,
+    /// Whether to display the time elapsed since the logger was built, or
+    /// since the last call to `reset_uptime()`
+    pub with_uptime_timestamp: bool,
 
-    /// Display levels as 5 or 3 letters
-    pub short_levels: bool,
+    /// How the levels are displayed
+    pub level_style: LevelStyle,
     /// Display the file calling the log macro
     pub with_file_name: bool,
     /// Display the line number calling the log macro
     pub with_line_number: bool,
+    /// Make the file names and line numbers clickable links to the source
+    /// files. When `None`, it is done when standard error is a terminal known
+    /// to support them.
+    ///
+    /// ```
+    /// use lovely_env_logger::Config;
+    ///
+    /// let config = Config {
+    ///     with_file_name: true,
+    ///     with_line_number: true,
+    ///     hyperlinks: Some(true),
+    ///     editor_url: Some("vscode://file/{path}:{line}".to_owned()),
+    ///     ..Config::default()
+    /// };
+    /// ```
+    pub hyperlinks: Option<bool>,
+    /// Template of the URL of the links, such as
+    /// `vscode://file/{path}:{line}`. The links are `file://` URLs otherwise.
+    pub editor_url: Option<String>,
+    /// Display the name of the thread calling the log macro. When padding is
+    /// enabled, the thread column is padded to its longest value seen so far,
+    /// whatever the padding strategy of the targets.
+    pub with_thread_name: bool,
+    /// Display the id of the thread calling the log macro
+    pub with_thread_id: bool,
     /// Pad the log line after module names
     pub with_padding: bool,
+    /// How the width of the padded module names is computed
+    pub padding: PaddingStrategy,
+    /// Format of the log lines
+    pub output: Output,
+    /// Prefix the lines with the `<N>` priority of sd-daemon, and disable
+    /// colors. When `None`, it is done when standard error is connected to
+    /// the systemd journal.
+    pub sd_prefix: Option<bool>,
+    /// Configuration of the syslog output
+    pub syslog: Syslog,
+    #[cfg(all(feature = "journald", target_os = "linux"))]
+    /// Configuration of the native journald output
+    pub journald: Journald,
+    /// Template of the log lines, replacing the default layout.
+    ///
+    /// The following placeholders are available:
+    /// - `{time}`: the timestamp, when enabled
+    /// - `{level}`: the log level
+    /// - `{thread}`: the thread name and id, when enabled
+    /// - `{target}`: the target of the log, usually the module path
+    /// - `{location}`: the file and line number, when enabled
+    /// - `{msg}`: the log message
+    /// - `{kv}`: the key-values of the log, each preceded by a space
+    ///
+    /// `{{` and `}}` are written as `{` and `}`.
+    pub format: Option<String>,
+    /// Styles of the different parts of the log lines
+    pub theme: Theme,
+    /// Give targets, or crates, their own color
+    pub color_targets: TargetColors,
+    /// Highlight the numbers, durations, quoted strings, file paths, IP
+    /// addresses and URLs found in the messages, with the styles of the
+    /// theme. Nothing is highlighted when colors are disabled.
+    pub highlight_values: bool,
+    #[cfg(feature = "regex")]
+    /// Rules highlighting the parts of the messages matching regular
+    /// expressions. They have priority over `highlight_values`.
+    pub highlight_rules: Vec<HighlightRule>,
+    #[cfg(feature = "regex")]
+    /// Dim the lines whose message does not match any of the
+    /// `highlight_rules`
+    pub dim_unmatched: bool,
+    /// How messages spanning multiple lines are displayed
+    pub multiline: Multiline,
+    #[cfg(feature = "wrap")]
+    /// Wrap the messages longer than the width of the terminal, when standard
+    /// error is a terminal
+    pub wrap: bool,
+    #[cfg(feature = "kv")]
+    /// How the key-values of the log records are displayed
+    pub kv: KvDisplay,
+    /// How targets longer than `max_target_width` are shortened
+    pub target_shortening: TargetShortening,
+    /// Maximum width of the targets, in characters
+    pub max_target_width: Option<usize>,
 }
 
 impl Default for Config {
@@ -118,10 +566,56 @@ impl Default for Config {
             with_system_timestamp: false,
             #[cfg(feature = "reltime")]
             reltime: false,
-            short_levels: false,
+            #[cfg(feature = "reltime")]
+            reltime_threshold: Duration::from_secs(10),
+            #[cfg(feature = "reltime")]
+            reltime_style: RelTimeStyle::Dmesg,
+            #[cfg(feature = "reltime")]
+            reltime_heat: false,
+            #[cfg(feature = "reltime")]
+            reltime_anchor_lines: None,
+            #[cfg(feature = "reltime")]
+            reltime_anchor_interval: None,
+            #[cfg(feature = "reltime")]
+            reltime_day_banner: false,
+            #[cfg(feature = "reltime")]
+            gap_marker: None,
+            #[cfg(any(feature = "humantime", feature = "reltime"))]
+            time_format: None,
+            #[cfg(any(feature = "humantime", feature = "reltime"))]
+            time_precision: None,
+            #[cfg(any(feature = "humantime", feature = "reltime"))]
+            timezone: None,
+            with_uptime_timestamp: false,
+            level_style: LevelStyle::Long,
             with_file_name: false,
             with_line_number: false,
+            hyperlinks: None,
+            editor_url: None,
+            with_thread_name: false,
+            with_thread_id: false,
             with_padding: false,
+            padding: PaddingStrategy::GrowOnly,
+            output: Output::Pretty,
+            sd_prefix: None,
+            syslog: Syslog::default(),
+            #[cfg(all(feature = "journald", target_os = "linux"))]
+            journald: Journald::default(),
+            format: None,
+            theme: Theme::default(),
+            color_targets: TargetColors::Off,
+            highlight_values: false,
+            #[cfg(feature = "regex")]
+            highlight_rules: Vec::new(),
+            #[cfg(feature = "regex")]
+            dim_unmatched: false,
+            multiline: Multiline::Off,
+            #[cfg(feature = "wrap")]
+            wrap: false,
+            #[cfg(feature = "kv")]
+            kv: KvDisplay::Inline,
+            target_shortening: TargetShortening::Truncate,
+            max_target_width: None,
         }
     }
 }
@@ -148,6 +642,23 @@ impl Config {
         }
     }
 
+    /// Whether any timestamp is displayed
+    #[inline]
+    fn is_timed(&self) -> bool {
+        if self.with_uptime_timestamp {
+            return true;
+        }
+        #[cfg(feature = "humantime")]
+        if self.with_system_timestamp {
+            return true;
+        }
+        #[cfg(feature = "reltime")]
+        if self.reltime {
+            return true;
+        }
+        false
+    }
+
     /// Creates a new Config for the lovely env logger,
     /// with values from the defined environment_variable_prefix, or from the
     /// fallback configuration
@@ -168,11 +679,95 @@ impl Config {
                 Some(v) => v == "1",
                 None => fallback_cfg.reltime,
             },
-            short_levels: match env::var_os(
-                environment_variable_prefix.to_owned() + "_SHORT_LEVELS",
+            #[cfg(feature = "reltime")]
+            reltime_threshold: match env::var(
+                environment_variable_prefix.to_owned() + "_RELTIME_THRESHOLD",
+            ) {
+                Ok(v) => v
+                    .parse()
+                    .ok()
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                    .unwrap_or(fallback_cfg.reltime_threshold),
+                Err(_) => fallback_cfg.reltime_threshold,
+            },
+            #[cfg(feature = "reltime")]
+            reltime_style: match env::var(environment_variable_prefix.to_owned() + "_RELTIME_STYLE")
+            {
+                Ok(v) => RelTimeStyle::parse(&v).unwrap_or(fallback_cfg.reltime_style),
+                Err(_) => fallback_cfg.reltime_style,
+            },
+            #[cfg(feature = "reltime")]
+            reltime_anchor_lines: match env::var(
+                environment_variable_prefix.to_owned() + "_RELTIME_ANCHOR_LINES",
+            ) {
+                Ok(v) => v.parse().ok().or(fallback_cfg.reltime_anchor_lines),
+                Err(_) => fallback_cfg.reltime_anchor_lines,
+            },
+            #[cfg(feature = "reltime")]
+            reltime_anchor_interval: match env::var(
+                environment_variable_prefix.to_owned() + "_RELTIME_ANCHOR_INTERVAL",
+            ) {
+                Ok(v) => v
+                    .parse()
+                    .ok()
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                    .or(fallback_cfg.reltime_anchor_interval),
+                Err(_) => fallback_cfg.reltime_anchor_interval,
+            },
+            #[cfg(feature = "reltime")]
+            reltime_day_banner: match env::var_os(
+                environment_variable_prefix.to_owned() + "_RELTIME_DAY_BANNER",
+            ) {
+                Some(v) => v == "1",
+                None => fallback_cfg.reltime_day_banner,
+            },
+            #[cfg(feature = "reltime")]
+            reltime_heat: match env::var_os(
+                environment_variable_prefix.to_owned() + "_RELTIME_HEAT",
+            ) {
+                Some(v) => v == "1",
+                None => fallback_cfg.reltime_heat,
+            },
+            #[cfg(feature = "reltime")]
+            gap_marker: match env::var(environment_variable_prefix.to_owned() + "_GAP_MARKER") {
+                Ok(v) => v
+                    .parse()
+                    .ok()
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                    .or(fallback_cfg.gap_marker),
+                Err(_) => fallback_cfg.gap_marker,
+            },
+            #[cfg(any(feature = "humantime", feature = "reltime"))]
+            time_format: match env::var(environment_variable_prefix.to_owned() + "_TIME_FORMAT") {
+                Ok(v) => Some(v),
+                Err(_) => fallback_cfg.time_format,
+            },
+            #[cfg(any(feature = "humantime", feature = "reltime"))]
+            time_precision: match env::var(
+                environment_variable_prefix.to_owned() + "_TIME_PRECISION",
+            ) {
+                Ok(v) => TimePrecision::parse(&v).or(fallback_cfg.time_precision),
+                Err(_) => fallback_cfg.time_precision,
+            },
+            #[cfg(any(feature = "humantime", feature = "reltime"))]
+            timezone: match env::var(environment_variable_prefix.to_owned() + "_TIMEZONE") {
+                Ok(v) => Timezone::parse(&v).or(fallback_cfg.timezone),
+                Err(_) => fallback_cfg.timezone,
+            },
+            with_uptime_timestamp: match env::var_os(
+                environment_variable_prefix.to_owned() + "_WITH_UPTIME_TIMESTAMPS",
             ) {
                 Some(v) => v == "1",
-                None => fallback_cfg.short_levels,
+                None => fallback_cfg.with_uptime_timestamp,
+            },
+            level_style: match (
+                env::var(environment_variable_prefix.to_owned() + "_LEVEL_STYLE"),
+                env::var_os(environment_variable_prefix.to_owned() + "_SHORT_LEVELS"),
+            ) {
+                (Ok(v), _) => LevelStyle::parse(&v).unwrap_or(fallback_cfg.level_style),
+                (Err(_), Some(v)) if v == "1" => LevelStyle::Short,
+                (Err(_), Some(_)) => LevelStyle::Long,
+                (Err(_), None) => fallback_cfg.level_style,
             },
             with_file_name: match env::var_os(
                 environment_variable_prefix.to_owned() + "_WITH_FILE_NAME",
@@ -186,12 +781,164 @@ impl Config {
                 Some(v) => v == "1",
                 None => fallback_cfg.with_line_number,
             },
+            hyperlinks: match env::var(environment_variable_prefix.to_owned() + "_HYPERLINKS") {
+                Ok(v) => match v.as_str() {
+                    "1" => Some(true),
+                    "0" => Some(false),
+                    "auto" => None,
+                    _ => fallback_cfg.hyperlinks,
+                },
+                Err(_) => fallback_cfg.hyperlinks,
+            },
+            editor_url: match env::var(environment_variable_prefix.to_owned() + "_EDITOR_URL") {
+                Ok(v) => Some(v),
+                Err(_) => fallback_cfg.editor_url,
+            },
+            with_thread_name: match env::var_os(
+                environment_variable_prefix.to_owned() + "_WITH_THREAD_NAME",
+            ) {
+                Some(v) => v == "1",
+                None => fallback_cfg.with_thread_name,
+            },
+            with_thread_id: match env::var_os(
+                environment_variable_prefix.to_owned() + "_WITH_THREAD_ID",
+            ) {
+                Some(v) => v == "1",
+                None => fallback_cfg.with_thread_id,
+            },
             with_padding: match env::var_os(
                 environment_variable_prefix.to_owned() + "_WITH_PADDING",
             ) {
                 Some(v) => v == "1",
                 None => fallback_cfg.with_padding,
             },
+            padding: match env::var(environment_variable_prefix.to_owned() + "_PADDING") {
+                Ok(v) => PaddingStrategy::parse(&v).unwrap_or(fallback_cfg.padding),
+                Err(_) => fallback_cfg.padding,
+            },
+            output: match env::var(environment_variable_prefix.to_owned() + "_OUTPUT") {
+                Ok(v) => Output::parse(&v).unwrap_or(fallback_cfg.output),
+                Err(_) => fallback_cfg.output,
+            },
+            sd_prefix: match env::var(environment_variable_prefix.to_owned() + "_SD_PREFIX") {
+                Ok(v) => match v.as_str() {
+                    "1" => Some(true),
+                    "0" => Some(false),
+                    "auto" => None,
+                    _ => fallback_cfg.sd_prefix,
+                },
+                Err(_) => fallback_cfg.sd_prefix,
+            },
+            syslog: Syslog {
+                format: match env::var(environment_variable_prefix.to_owned() + "_SYSLOG_FORMAT") {
+                    Ok(v) => SyslogFormat::parse(&v).unwrap_or(fallback_cfg.syslog.format),
+                    Err(_) => fallback_cfg.syslog.format,
+                },
+                facility: match env::var(
+                    environment_variable_prefix.to_owned() + "_SYSLOG_FACILITY",
+                ) {
+                    Ok(v) => Facility::parse(&v).unwrap_or(fallback_cfg.syslog.facility),
+                    Err(_) => fallback_cfg.syslog.facility,
+                },
+                app_name: match env::var(
+                    environment_variable_prefix.to_owned() + "_SYSLOG_APP_NAME",
+                ) {
+                    Ok(v) => Some(v),
+                    Err(_) => fallback_cfg.syslog.app_name,
+                },
+                hostname: match env::var(
+                    environment_variable_prefix.to_owned() + "_SYSLOG_HOSTNAME",
+                ) {
+                    Ok(v) => Some(v),
+                    Err(_) => fallback_cfg.syslog.hostname,
+                },
+                socket: match env::var_os(environment_variable_prefix.to_owned() + "_SYSLOG_SOCKET")
+                {
+                    Some(v) => Some(v.into()),
+                    None => fallback_cfg.syslog.socket,
+                },
+            },
+            #[cfg(all(feature = "journald", target_os = "linux"))]
+            journald: Journald {
+                mode: match env::var(environment_variable_prefix.to_owned() + "_JOURNALD") {
+                    Ok(v) => JournaldMode::parse(&v).unwrap_or(fallback_cfg.journald.mode),
+                    Err(_) => fallback_cfg.journald.mode,
+                },
+                socket: match env::var_os(
+                    environment_variable_prefix.to_owned() + "_JOURNALD_SOCKET",
+                ) {
+                    Some(v) => v.into(),
+                    None => fallback_cfg.journald.socket,
+                },
+            },
+            format: match env::var(environment_variable_prefix.to_owned() + "_FORMAT") {
+                Ok(v) => Some(v),
+                Err(_) => fallback_cfg.format,
+            },
+            theme: {
+                let mut theme = match env::var(environment_variable_prefix.to_owned() + "_THEME") {
+                    Ok(v) => Theme::from_name(&v).unwrap_or(fallback_cfg.theme),
+                    Err(_) => fallback_cfg.theme,
+                };
+                if let Ok(v) = env::var(environment_variable_prefix.to_owned() + "_COLORS") {
+                    theme.apply_colors(&v);
+                }
+                theme
+            },
+            color_targets: match env::var(environment_variable_prefix.to_owned() + "_COLOR_TARGETS")
+            {
+                Ok(v) => match v.as_str() {
+                    "1" | "target" => TargetColors::Target,
+                    "crate" => TargetColors::Crate,
+                    "0" | "off" => TargetColors::Off,
+                    _ => fallback_cfg.color_targets,
+                },
+                Err(_) => fallback_cfg.color_targets,
+            },
+            highlight_values: match env::var_os(
+                environment_variable_prefix.to_owned() + "_HIGHLIGHT_VALUES",
+            ) {
+                Some(v) => v == "1",
+                None => fallback_cfg.highlight_values,
+            },
+            #[cfg(feature = "regex")]
+            highlight_rules: match env::var(environment_variable_prefix.to_owned() + "_HIGHLIGHT") {
+                Ok(v) => HighlightRule::parse_all(&v),
+                Err(_) => fallback_cfg.highlight_rules,
+            },
+            #[cfg(feature = "regex")]
+            dim_unmatched: match env::var_os(
+                environment_variable_prefix.to_owned() + "_HIGHLIGHT_DIM",
+            ) {
+                Some(v) => v == "1",
+                None => fallback_cfg.dim_unmatched,
+            },
+            multiline: match env::var(environment_variable_prefix.to_owned() + "_MULTILINE") {
+                Ok(v) => Multiline::parse(&v).unwrap_or(fallback_cfg.multiline),
+                Err(_) => fallback_cfg.multiline,
+            },
+            #[cfg(feature = "wrap")]
+            wrap: match env::var_os(environment_variable_prefix.to_owned() + "_WRAP") {
+                Some(v) => v == "1",
+                None => fallback_cfg.wrap,
+            },
+            #[cfg(feature = "kv")]
+            kv: match env::var(environment_variable_prefix.to_owned() + "_KV") {
+                Ok(v) => KvDisplay::parse(&v).unwrap_or(fallback_cfg.kv),
+                Err(_) => fallback_cfg.kv,
+            },
+            target_shortening: match env::var(
+                environment_variable_prefix.to_owned() + "_TARGET_SHORTENING",
+            ) {
+                Ok(v) => TargetShortening::parse(&v).unwrap_or(fallback_cfg.target_shortening),
+                Err(_) => fallback_cfg.target_shortening,
+            },
+            max_target_width: match env::var(
+                environment_variable_prefix.to_owned() + "_MAX_TARGET_WIDTH",
+            ) {
+                Ok(v) => v.parse().ok().or(fallback_cfg.max_target_width),
+                Err(_) => fallback_cfg.max_target_width,
+            },
         }
     }
 }
@@ -277,8 +1024,9 @@ pub fn try_init_custom_env(
     environment_variable_name: &str,
 ) -> Result<(), log::SetLoggerError> {
     let cfg = Config::from_environment_variables(environment_variable_name, config);
-    let mut builder = formatted_builder(cfg);
-    if let Ok(s) = ::std::env::var(environment_variable_name) {
+    let filters = ::std::env::var(environment_variable_name).ok();
+    let mut builder = build_formatted(cfg, filters.as_deref());
+    if let Some(s) = filters {
         builder.parse_filters(&s);
     }
     builder.try_init()
@@ -289,92 +1037,378 @@ pub fn try_init_custom_env(
 /// This method will return a colored and formatted `env_logger::Builder`
 /// for further customization. Refer to env_logger::Build crate documentation
 /// for further details and usage.
+///
+/// Each builder keeps its own padding state.
+///
+/// The native journald protocol is only used when `JournaldMode::Always` is
+/// set, since the target and the write style of the builder may be changed
+/// afterwards.
 pub fn formatted_builder(config: Config) -> Builder {
+    #[cfg(all(feature = "journald", target_os = "linux"))]
+    let config = Config {
+        journald: Journald {
+            mode: match config.journald.mode {
+                JournaldMode::Auto => JournaldMode::Off,
+                mode => mode,
+            },
+            ..config.journald
+        },
+        ..config
+    };
+    let filters = env::var(RUST_LOG_ENV).ok();
+    build_formatted(config, filters.as_deref())
+}
+
+/// Returns a formatted `env_logger::Builder`, for a logger using the given
+/// filter directives
+fn build_formatted(config: Config, filters: Option<&str>) -> Builder {
     let mut builder = Builder::new();
+    let padding = Padding::new(&config, filters);
+    let thread_padding = Padding::grow_only();
+    #[cfg(feature = "regex")]
+    let dimmed_theme = Theme::dimmed();
+    let level_width = config.level_style.width();
+    let hyperlinks = Hyperlinks::new(config.hyperlinks, config.editor_url.as_deref());
+    let template = match config.format {
+        Some(ref format) => Template::parse(format),
+        None => Template::parse(&default_format(&config)),
+    };
+    let start = std::time::Instant::now();
+    #[cfg(feature = "reltime")]
+    let last_time = Arc::new(Mutex::new(None));
     #[cfg(feature = "reltime")]
-    let last_time = Arc::new(Mutex::new(Local::now()));
+    let anchor = Mutex::new(reltime::Anchor::new());
+    #[cfg(feature = "humantime")]
+    let system_times = TimeFormatter::new(
+        config.time_format.as_deref(),
+        config.timezone.unwrap_or(Timezone::Utc),
+        config.time_precision.unwrap_or(TimePrecision::Millis),
+    );
+    #[cfg(feature = "reltime")]
+    let dates = TimeFormatter::new(
+        Some(config.time_format.as_deref().unwrap_or("%b%e %T%.f")),
+        config.timezone.unwrap_or(Timezone::Local),
+        config.time_precision.unwrap_or(TimePrecision::Seconds),
+    );
+    let syslog = (config.output == Output::Syslog).then(|| config.syslog.header());
+    #[cfg(unix)]
+    if let (Some(_), Some(path)) = (&syslog, &config.syslog.socket) {
+        // Keep writing to the default target when the socket is unavailable
+        if let Ok(socket) = syslog::SyslogSocket::connect(path.clone()) {
+            builder.target(env_logger::Target::Pipe(Box::new(socket)));
+        }
+    }
+
+    #[cfg(all(feature = "journald", target_os = "linux"))]
+    let journald = match config.journald.connect(config.output == Output::Pretty) {
+        Some(socket) => {
+            builder.target(env_logger::Target::Pipe(Box::new(socket)));
+            // Keep the binary fields from being altered when colors are
+            // stripped
+            builder.write_style(env_logger::WriteStyle::Always);
+            true
+        }
+        None => false,
+    };
+    let sd_prefix = match config.sd_prefix {
+        Some(sd_prefix) => sd_prefix,
+        None => systemd::stderr_is_journal(),
+    } && config.output != Output::Syslog;
+    #[cfg(all(feature = "journald", target_os = "linux"))]
+    let sd_prefix = sd_prefix && !journald;
+    if sd_prefix {
+        builder.write_style(env_logger::WriteStyle::Never);
+    }
+    #[cfg(feature = "wrap")]
+    let wrap = config.wrap && std::io::stderr().is_terminal();
 
     builder.format(move |f, record| {
         use std::io::Write;
 
-        let (target, location) = compute_target_and_location(record, &config);
+        #[cfg(all(feature = "journald", target_os = "linux"))]
+        if journald {
+            return journald::write(f, record);
+        }
+        if sd_prefix && config.output != Output::Pretty {
+            write!(f, "<{}>", syslog::severity(record.level()))?;
+        }
+
+        match config.output {
+            Output::Pretty => {}
+            Output::Json => return json::write(f, record, &config),
+            Output::Logfmt => return logfmt::write(f, record, &config),
+            Output::Syslog => {
+                if let Some(ref syslog) = syslog {
+                    return syslog.write(f, record);
+                }
+            }
+        }
+
+        let (target, location) = compute_target_and_location(record, &config, &padding);
+        let thread = compute_thread(&config, &thread_padding);
 
+        let text = record.args().to_string();
+        // Highlighting is useless when colors are stripped
+        let colors = !f.default_level_style(Level::Error).is_plain();
+        #[cfg(feature = "regex")]
+        let rule_spans = if colors {
+            highlight::match_rules(&config.highlight_rules, &text)
+        } else {
+            Vec::new()
+        };
+        #[cfg(not(feature = "regex"))]
+        let rule_spans = Vec::new();
+        #[cfg(feature = "regex")]
+        let theme = if colors
+            && config.dim_unmatched
+            && !config.highlight_rules.is_empty()
+            && rule_spans.is_empty()
+        {
+            &dimmed_theme
+        } else {
+            &config.theme
+        };
+        #[cfg(not(feature = "regex"))]
+        let theme = &config.theme;
         let level = LevelStr {
-            level: record.level(),
-            short: config.short_levels,
+            label: config.level_style.label(record.level()),
+            width: level_width,
+            style: theme.level(record.level()),
         };
 
-        let bold = Style::new().bold();
         #[cfg(feature = "reltime")]
-        {
-            if config.reltime {
-                let reltime = compute_reltime(&last_time);
-                let reltime_style = if reltime.is_delta() {
-                    Style::new()
-                } else {
-                    bold
-                };
+        let gap = (config.reltime || config.gap_marker.is_some())
+            .then(|| elapsed(&last_time))
+            .flatten();
+        #[cfg(feature = "reltime")]
+        let (reltime, new_day) = if config.reltime {
+            // The first log is relative to the creation of the logger
+            let diff = gap.unwrap_or_else(|| start.elapsed());
+            let (reltime, new_day) =
+                compute_reltime(diff, chrono::Utc::now(), &config, &dates, &anchor);
+            (Some(reltime), new_day)
+        } else {
+            (None, None)
+        };
 
-                return writeln!(
-                    f,
-                    "{}{}{} {} {}{}{}{} {}",
-                    reltime_style.render(),
-                    reltime,
-                    reltime_style.render_reset(),
-                    level,
-                    bold.render(),
-                    target,
-                    bold.render_reset(),
-                    location,
-                    record.args(),
-                );
+        // The fields before the message, and the fields after it
+        let mut prefix = Vec::new();
+        let mut suffix = Vec::new();
+        let mut message = None;
+        // Column of the message, once the fields before it are rendered
+        #[cfg(feature = "kv")]
+        let mut column = 0;
+        for field in template.fields() {
+            let out = if message.is_none() {
+                &mut prefix
+            } else {
+                &mut suffix
+            };
+            match field {
+                Field::Literal(text) => write!(out, "{text}")?,
+                Field::Time => {
+                    let mut times = Vec::new();
+                    #[cfg(feature = "humantime")]
+                    if config.with_system_timestamp {
+                        let value = system_times.format(chrono::Utc::now());
+                        let time = Styled {
+                            style: theme.timestamp,
+                            value,
+                        };
+                        times.push(time.to_string());
+                    }
+                    if config.with_uptime_timestamp {
+                        let uptime = Styled {
+                            style: theme.timestamp,
+                            value: uptime::uptime(start),
+                        };
+                        times.push(uptime.to_string());
+                    }
+                    #[cfg(feature = "reltime")]
+                    if let Some(ref reltime) = reltime {
+                        let style = match gap {
+                            Some(gap) if config.reltime_heat => reltime::heat(theme, gap),
+                            _ => theme.timestamp,
+                        };
+                        let style = if reltime.is_delta() {
+                            style
+                        } else {
+                            style.bold()
+                        };
+                        times.push(
+                            Styled {
+                                style,
+                                value: reltime,
+                            }
+                            .to_string(),
+                        );
+                    }
+                    write!(out, "{}", times.join(" "))?;
+                }
+                Field::Level => write!(out, "{level}")?,
+                Field::Thread => {
+                    if let Some((ref name, ref thread)) = thread {
+                        write!(
+                            out,
+                            "{}",
+                            Styled {
+                                style: config.color_targets.style(theme, name),
+                                value: thread,
+                            }
+                        )?;
+                    }
+                }
+                Field::Target => write!(
+                    out,
+                    "{}",
+                    Styled {
+                        style: config.color_targets.style(theme, record.target()),
+                        value: &target,
+                    }
+                )?,
+                Field::Location => match (&location, &hyperlinks, record.file()) {
+                    (OptionalPadded::None, _, _) => {}
+                    (OptionalPadded::Some { value, width }, Some(hyperlinks), Some(file)) => {
+                        // Only the file name and line number are clickable,
+                        // not the separator nor the padding
+                        let url = hyperlinks.url(file, record.line());
+                        let link = Hyperlink {
+                            url: &url,
+                            text: &value[1..],
+                        };
+                        write!(
+                            out,
+                            "{}{}{:pad$}",
+                            Styled {
+                                style: theme.location,
+                                value: ':',
+                            },
+                            Styled {
+                                style: theme.location,
+                                value: link,
+                            },
+                            "",
+                            pad = width.saturating_sub(value.chars().count()),
+                        )?;
+                    }
+                    (OptionalPadded::Some { .. }, _, _) => write!(
+                        out,
+                        "{}",
+                        Styled {
+                            style: theme.location,
+                            value: &location,
+                        }
+                    )?,
+                },
+                Field::Message => match message {
+                    None => {
+                        message = Some(&text);
+                        #[cfg(feature = "kv")]
+                        {
+                            column = visible_width(&prefix);
+                        }
+                    }
+                    Some(message) => write!(
+                        out,
+                        "{}",
+                        Styled {
+                            style: theme.message,
+                            value: message,
+                        }
+                    )?,
+                },
+                Field::Kv => {
+                    #[cfg(feature = "kv")]
+                    config.kv.write(out, record, theme, column)?;
+                }
             }
         }
-        #[cfg(feature = "humantime")]
-        {
-            if config.with_system_timestamp {
-                let time = f.timestamp_millis();
-                return writeln!(
-                    f,
-                    "{} {} {}{}{}{} {}",
-                    time,
-                    level,
-                    bold.render(),
-                    target,
-                    bold.render_reset(),
-                    location,
-                    record.args(),
-                );
+
+        // The terminal may have been resized since the previous line
+        #[cfg(feature = "wrap")]
+        let terminal_width = if wrap { wrap::terminal_width() } else { None };
+        #[cfg(not(feature = "wrap"))]
+        let terminal_width = None;
+        let mut lines = Vec::new();
+        #[cfg(feature = "reltime")]
+        if let (Some(threshold), Some(gap)) = (config.gap_marker, gap) {
+            if gap > threshold {
+                let marker = Styled {
+                    style: if config.reltime_heat {
+                        reltime::heat(theme, gap)
+                    } else {
+                        theme.timestamp
+                    },
+                    value: reltime::gap_marker(gap),
+                };
+                writeln!(lines, "{marker}")?;
             }
         }
-        if config.with_padding {
-            writeln!(
-                f,
-                "{} {}{}{}{} > {}",
-                level,
-                bold.render(),
-                target,
-                bold.render_reset(),
-                location,
-                record.args(),
-            )
+        #[cfg(feature = "reltime")]
+        if let Some(day) = new_day {
+            let banner = Styled {
+                style: theme.timestamp.bold(),
+                value: reltime::day_banner(day),
+            };
+            writeln!(lines, "{banner}")?;
+        }
+        match message {
+            Some(message) => {
+                let mut message = Highlighted::new(message, theme.message, rule_spans);
+                if config.highlight_values && colors {
+                    message.highlight_values(theme);
+                }
+                config
+                    .multiline
+                    .write(&mut lines, &prefix, &suffix, &message, terminal_width)?
+            }
+            None => {
+                lines.extend(prefix);
+                lines.push(b'\n');
+            }
+        }
+        if sd_prefix {
+            // Colors are stripped even when the write style of the builder
+            // was changed
+            if colors {
+                lines = multiline::strip_escapes(&lines);
+            }
+            // Every line is read as a separate entry by the journal
+            let severity = syslog::severity(record.level());
+            for line in lines.split_inclusive(|&byte| byte == b'\n') {
+                write!(f, "<{severity}>")?;
+                f.write_all(line)?;
+            }
+            Ok(())
         } else {
-            writeln!(
-                f,
-                "{} {}{}{}{} {}",
-                level,
-                bold.render(),
-                target,
-                bold.render_reset(),
-                location,
-                record.args(),
-            )
+            f.write_all(&lines)
         }
     });
 
     builder
 }
 
+/// Returns the template matching the configuration, when none is set.
+///
+/// Columns are always in the same order: timestamps, level, thread, target
+/// and location, the padding separator, the message, then the key-values.
+fn default_format(config: &Config) -> String {
+    let mut format = String::new();
+    if config.is_timed() {
+        format.push_str("{time} ");
+    }
+    format.push_str("{level} ");
+    if config.with_thread_name || config.with_thread_id {
+        format.push_str("{thread} ");
+    }
+    format.push_str("{target}{location}");
+    if config.with_padding {
+        format.push_str(" >");
+    }
+    format.push_str(" {msg}{kv}");
+    format
+}
+
 struct Padded<T> {
     value: T,
     width: usize,
@@ -401,13 +1435,14 @@ impl<T: fmt::Display> fmt::Display for OptionalPadded<T> {
     }
 }
 
-static MAX_MODULE_WIDTH: AtomicUsize = AtomicUsize::new(0);
-
 fn compute_target_and_location<'a>(
     record: &log::Record<'a>,
     config: &Config,
-) -> (Padded<&'a str>, OptionalPadded<String>) {
-    let target = record.target();
+    padding: &Padding,
+) -> (Padded<Cow<'a, str>>, OptionalPadded<String>) {
+    let target = config
+        .target_shortening
+        .shorten(record.target(), config.max_target_width);
     let opt_file = if config.with_file_name {
         record.file()
     } else {
@@ -418,7 +1453,7 @@ fn compute_target_and_location<'a>(
     } else {
         None
     };
-    let target_len = target.len();
+    let target_len = target.chars().count();
     let (added_opt, added_len) = match (opt_file, opt_line) {
         (None, None) => (None, 0),
         (Some(file), None) => (Some(format!(":{file}")), file.len() + 1),
@@ -435,7 +1470,7 @@ fn compute_target_and_location<'a>(
         }
     };
     let full_width = if config.with_padding {
-        max_target_width(target_len + added_len)
+        padding.width(target_len + added_len)
     } else {
         target_len + added_len
     };
@@ -446,7 +1481,7 @@ fn compute_target_and_location<'a>(
         };
         let location_padded = OptionalPadded::Some {
             value: added,
-            width: full_width - target_len,
+            width: full_width.saturating_sub(target_len),
         };
         (target_padded, location_padded)
     } else {
@@ -457,113 +1492,616 @@ fn compute_target_and_location<'a>(
         (target_padded, OptionalPadded::None)
     }
 }
-fn max_target_width(target_len: usize) -> usize {
-    let max_width = MAX_MODULE_WIDTH.load(Ordering::Relaxed);
-    if max_width < target_len {
-        MAX_MODULE_WIDTH.store(target_len, Ordering::Relaxed);
-        target_len
-    } else {
-        max_width
+
+/// Returns the name of the current thread, and the padded thread column,
+/// displayed as `name#id`
+fn compute_thread(config: &Config, padding: &Padding) -> Option<(String, Padded<String>)> {
+    if !config.with_thread_name && !config.with_thread_id {
+        return None;
     }
+    let current = thread::current();
+    let name = current.name().unwrap_or("<unnamed>").to_owned();
+    let mut value = String::new();
+    if config.with_thread_name {
+        value.push_str(&name);
+    }
+    if config.with_thread_id {
+        value.push('#');
+        value.push_str(&thread_id(&current));
+    }
+    let len = value.chars().count();
+    let width = if config.with_padding {
+        padding.width(len)
+    } else {
+        len
+    };
+    Some((name, Padded { value, width }))
+}
+
+/// Returns the numeric id of a thread
+fn thread_id(thread: &thread::Thread) -> String {
+    // `ThreadId` is only displayed through `Debug`, as `ThreadId(N)`
+    let id = format!("{:?}", thread.id());
+    id.trim_start_matches("ThreadId(")
+        .trim_end_matches(')')
+        .to_owned()
+}
+
+/// Returns the name of the host, read from `/proc/sys/kernel/hostname`
+fn hostname() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .map(|hostname| hostname.trim().to_owned())
+        .filter(|hostname| !hostname.is_empty())
 }
 
 /// Struct to display a colored log level
-struct LevelStr {
-    /// The level to display
-    level: Level,
-    /// Whether to display the level on 3 characters
-    short: bool,
+struct LevelStr<'a> {
+    /// The label of the level
+    label: &'a str,
+    /// The width of the widest label
+    width: usize,
+    /// The style of the level
+    style: Style,
 }
-impl fmt::Display for LevelStr {
+impl fmt::Display for LevelStr<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let (style, lvl) = match (self.level, self.short) {
-            (Level::Trace, false) => (
-                Style::new()
-                    .bold()
-                    .fg_color(Some(AnsiColor::Magenta.into())),
-                "TRACE",
-            ),
-            (Level::Trace, true) => (
-                Style::new()
-                    .bold()
-                    .fg_color(Some(AnsiColor::Magenta.into())),
-                "TRC",
-            ),
-            (Level::Debug, false) => (Style::new().fg_color(Some(AnsiColor::Blue.into())), "DEBUG"),
-            (Level::Debug, true) => (Style::new().fg_color(Some(AnsiColor::Blue.into())), "DBG"),
-            (Level::Info, false) => (
-                Style::new().fg_color(Some(AnsiColor::Green.into())),
-                "INFO ",
-            ),
-            (Level::Info, true) => (Style::new().fg_color(Some(AnsiColor::Green.into())), "INF"),
-            (Level::Warn, false) => (
-                Style::new().fg_color(Some(AnsiColor::Yellow.into())),
-                "WARN ",
-            ),
-            (Level::Warn, true) => (Style::new().fg_color(Some(AnsiColor::Yellow.into())), "WRN"),
-            (Level::Error, false) => (
-                Style::new().bold().fg_color(Some(AnsiColor::Red.into())),
-                "ERROR",
-            ),
-            (Level::Error, true) => (
-                Style::new().bold().fg_color(Some(AnsiColor::Red.into())),
-                "ERR",
-            ),
-        };
-        write!(f, "{}{}{}", style.render(), lvl, style.render_reset())
+        write!(
+            f,
+            "{}{: <width$}{}",
+            self.style.render(),
+            self.label,
+            self.style.render_reset(),
+            width = self.width,
+        )
+    }
+}
+
+/// Struct to display a value with a style
+struct Styled<T> {
+    /// The style to apply
+    style: Style,
+    /// The value to display
+    value: T,
+}
+impl<T: fmt::Display> fmt::Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.style.render(),
+            self.value,
+            self.style.render_reset()
+        )
     }
 }
 
 #[cfg(feature = "reltime")]
 enum RelTime {
-    Diff(u32),
-    DateTime(DateTime<Local>),
+    Diff(Duration, RelTimeStyle),
+    DateTime(String),
 }
 
 #[cfg(feature = "reltime")]
 impl RelTime {
     #[inline]
     fn is_delta(&self) -> bool {
-        matches!(self, Self::Diff(_))
+        matches!(self, Self::Diff(..))
     }
 }
 #[cfg(feature = "reltime")]
 impl fmt::Display for RelTime {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Diff(diff) => {
-                write!(f, "[  +0.{diff:0>9}]")
-            }
-            Self::DateTime(dt) => {
-                write!(f, "[{}]", dt.format("%b%e %T"))
-            }
+            Self::Diff(diff, style) => f.write_str(&style.format(*diff)),
+            Self::DateTime(dt) => write!(f, "[{dt}]"),
         }
     }
 }
 
+/// Returns the time elapsed since the previous log, if any, measured with a
+/// monotonic clock
+#[cfg(feature = "reltime")]
+fn elapsed(last_time: &Arc<Mutex<Option<Instant>>>) -> Option<Duration> {
+    let now = Instant::now();
+    let previous = last_time.lock().unwrap().replace(now)?;
+    Some(now.saturating_duration_since(previous))
+}
+
+/// Returns the time elapsed since the previous log, or the date of the log
+/// when it is larger than the threshold, when an anchor is due or when the day
+/// changed, along with the day of the log when it changed and day banners are
+/// displayed
 #[cfg(feature = "reltime")]
-fn compute_reltime(last_time: &Arc<Mutex<DateTime<Local>>>) -> RelTime {
-    let mut old = last_time.lock().unwrap();
-    let old_date = old.date_naive();
-    let old_time = old.time();
-    let now = Local::now();
-    let now_date = now.date_naive();
-    let now_time = now.time();
-    let reltime = if old_date == now_date
-        && old_time.hour() == now_time.hour()
-        && old_time.minute() == now_time.minute()
-        && old_time.second() == now_time.second()
+fn compute_reltime(
+    diff: Duration,
+    now: chrono::DateTime<chrono::Utc>,
+    config: &Config,
+    dates: &TimeFormatter,
+    anchor: &Mutex<reltime::Anchor>,
+) -> (RelTime, Option<chrono::NaiveDate>) {
+    let mut anchor = anchor.lock().unwrap();
+    // Any anchor also displays the date of the first log of each day
+    let anchored = config.reltime_day_banner
+        || config.reltime_anchor_lines.is_some()
+        || config.reltime_anchor_interval.is_some();
+    let new_day = anchor.new_day(anchored.then(|| dates.day(now)));
+    let banner = new_day.filter(|_| config.reltime_day_banner);
+    if diff > config.reltime_threshold
+        || new_day.is_some()
+        || anchor.is_due(config.reltime_anchor_lines, config.reltime_anchor_interval)
     {
-        let old_ns = old_time.nanosecond();
-        let now_ns = now_time.nanosecond();
-        if old_ns > now_ns {
-            RelTime::Diff(0)
-        } else {
-            RelTime::Diff(now_ns - old_ns)
-        }
+        anchor.displayed();
+        (RelTime::DateTime(dates.format(now)), banner)
     } else {
-        RelTime::DateTime(now)
-    };
-    *old = now;
-    reltime
+        anchor.skipped();
+        (RelTime::Diff(diff, config.reltime_style), banner)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+
+    use env_logger::{Target, WriteStyle};
+    use log::{Level, LevelFilter, Log};
+
+    use super::*;
+
+    /// A logger built from a configuration, writing to a buffer
+    pub(crate) struct Capture {
+        /// What was logged so far
+        output: Arc<Mutex<Vec<u8>>>,
+        /// The logger
+        logger: env_logger::Logger,
+    }
+
+    /// Writer appending to the buffer of a `Capture`
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Capture {
+        /// Builds a logger with colors disabled
+        pub(crate) fn new(config: Config) -> Self {
+            Self::with_style(config, WriteStyle::Never)
+        }
+
+        /// Builds a logger with colors enabled
+        pub(crate) fn colored(config: Config) -> Self {
+            Self::with_style(config, WriteStyle::Always)
+        }
+
+        /// Builds a logger with the given write style
+        fn with_style(mut config: Config, write_style: WriteStyle) -> Self {
+            // The output must not depend on whether the tests run under
+            // systemd
+            config.sd_prefix = config.sd_prefix.or(Some(false));
+            let output = Arc::new(Mutex::new(Vec::new()));
+            let logger = formatted_builder(config)
+                .target(Target::Pipe(Box::new(Buffer(Arc::clone(&output)))))
+                .write_style(write_style)
+                .filter_level(LevelFilter::Trace)
+                .build();
+            Self { output, logger }
+        }
+
+        /// Logs a message of the `app` target, from `src/main.rs:42`
+        pub(crate) fn log(&self, level: Level, message: &str) {
+            self.log_to(level, "app", message);
+        }
+
+        /// Logs a message of a target, from `src/main.rs:42`
+        pub(crate) fn log_to(&self, level: Level, target: &str, message: &str) {
+            self.logger.log(
+                &log::Record::builder()
+                    .level(level)
+                    .target(target)
+                    .file(Some("src/main.rs"))
+                    .line(Some(42))
+                    .args(format_args!("{message}"))
+                    .build(),
+            );
+        }
+
+        /// Logs an info message of the `app` target with key-values
+        #[cfg(feature = "kv")]
+        pub(crate) fn log_kv(&self, message: &str, key_values: &[(&str, log::kv::Value)]) {
+            self.logger.log(
+                &log::Record::builder()
+                    .level(Level::Info)
+                    .target("app")
+                    .key_values(&key_values)
+                    .args(format_args!("{message}"))
+                    .build(),
+            );
+        }
+
+        /// Returns what was logged so far
+        pub(crate) fn output(&self) -> String {
+            String::from_utf8(self.output.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    /// Returns the line logged by a logger built from a configuration
+    fn render(config: Config) -> String {
+        let capture = Capture::new(config);
+        capture.log(Level::Info, "hello");
+        capture.output()
+    }
+
+    #[test]
+    fn padding_and_location_combine() {
+        for with_padding in [false, true] {
+            for with_file_name in [false, true] {
+                for with_line_number in [false, true] {
+                    let line = render(Config {
+                        with_padding,
+                        with_file_name,
+                        with_line_number,
+                        ..Config::default()
+                    });
+                    let location = match (with_file_name, with_line_number) {
+                        (false, false) => "",
+                        (true, false) => ":src/main.rs",
+                        (false, true) => ":42",
+                        (true, true) => ":src/main.rs:42",
+                    };
+                    let separator = if with_padding { " >" } else { "" };
+                    assert_eq!(line, format!("INFO  app{location}{separator} hello\n"));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "humantime", feature = "reltime"))]
+    fn timestamps_and_padding_combine() {
+        for with_padding in [false, true] {
+            for with_system_timestamp in [false, true] {
+                for reltime in [false, true] {
+                    let line = render(Config {
+                        with_system_timestamp,
+                        reltime,
+                        with_padding,
+                        ..Config::default()
+                    });
+                    let mut columns = line.split_whitespace();
+                    if with_system_timestamp {
+                        // e.g. 2024-01-31T12:34:56.789Z
+                        let time = columns.next().unwrap();
+                        assert!(time.ends_with('Z') && time.contains('T'), "{line}");
+                    }
+                    if reltime {
+                        // e.g. [  +0.000012345], or the date of the log
+                        let end = line.find(']').unwrap();
+                        let time = &line[line.find('[').unwrap()..=end];
+                        assert!(line.contains(&format!("{time} INFO  app")), "{line}");
+                        columns = line[end + 1..].split_whitespace();
+                    }
+                    assert_eq!(columns.next(), Some("INFO"));
+                    assert_eq!(columns.next(), Some("app"));
+                    if with_padding {
+                        assert_eq!(columns.next(), Some(">"));
+                    }
+                    assert_eq!(columns.next(), Some("hello"));
+                    assert_eq!(columns.next(), None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "reltime")]
+    fn reltime_threshold() {
+        let config = Config {
+            reltime: true,
+            reltime_threshold: Duration::from_secs(5),
+            ..Config::default()
+        };
+        let dates = TimeFormatter::new(Some("%b%e %T%.f"), Timezone::Local, TimePrecision::Seconds);
+        let anchor = Mutex::new(reltime::Anchor::new());
+        let reltime = |diff| compute_reltime(diff, chrono::Utc::now(), &config, &dates, &anchor).0;
+
+        let diff = reltime(Duration::from_millis(1100));
+        assert!(diff.is_delta());
+        assert_eq!(diff.to_string(), "[  +1.100000000]");
+        assert_eq!(
+            reltime(Duration::from_secs(5)).to_string(),
+            "[  +5.000000000]"
+        );
+        assert!(!reltime(Duration::from_millis(5001)).is_delta());
+    }
+
+    #[test]
+    #[cfg(feature = "reltime")]
+    fn reltime_day_change() {
+        use chrono::TimeZone;
+
+        let day = |day| {
+            chrono::Utc
+                .with_ymd_and_hms(2026, 10, day, 23, 0, 0)
+                .unwrap()
+        };
+        let dates = TimeFormatter::new(None, Timezone::Utc, TimePrecision::Seconds);
+        let anchors = Config {
+            reltime: true,
+            reltime_anchor_lines: Some(100),
+            ..Config::default()
+        };
+        let banners = Config {
+            reltime: true,
+            reltime_day_banner: true,
+            ..Config::default()
+        };
+        for config in [anchors, banners] {
+            let anchor = Mutex::new(reltime::Anchor::new());
+            let reltime =
+                |now| compute_reltime(Duration::from_millis(1), now, &config, &dates, &anchor);
+
+            assert_eq!(reltime(day(15)).0.is_delta(), config.reltime_day_banner);
+            assert!(reltime(day(15)).0.is_delta());
+            let (time, banner) = reltime(day(16));
+            assert_eq!(time.to_string(), "[2026-10-16T23:00:00Z]");
+            assert_eq!(
+                banner,
+                config.reltime_day_banner.then(|| day(16).date_naive())
+            );
+            assert!(reltime(day(16)).0.is_delta());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "reltime")]
+    fn reltime_anchor_lines() {
+        let capture = Capture::new(Config {
+            reltime: true,
+            reltime_anchor_lines: Some(3),
+            ..Config::default()
+        });
+        for _ in 0..5 {
+            capture.log(Level::Info, "hello");
+        }
+
+        let output = capture.output();
+        let deltas: Vec<_> = output
+            .lines()
+            .map(|line| line.starts_with("[  +"))
+            .collect();
+        assert_eq!(deltas, [false, true, true, false, true]);
+    }
+
+    #[test]
+    #[cfg(feature = "reltime")]
+    fn gap_marker() {
+        let capture = Capture::new(Config {
+            gap_marker: Some(Duration::from_secs(3600)),
+            ..Config::default()
+        });
+        capture.log(Level::Info, "hello");
+        capture.log(Level::Info, "hello");
+        assert_eq!(capture.output(), "INFO  app hello\nINFO  app hello\n");
+
+        let capture = Capture::new(Config {
+            gap_marker: Some(Duration::ZERO),
+            ..Config::default()
+        });
+        capture.log(Level::Info, "hello");
+        capture.log(Level::Info, "hello");
+        let output = capture.output();
+        let lines: Vec<_> = output.lines().collect();
+        // No gap before the first log, however long after the creation of the
+        // logger
+        assert_eq!(lines.len(), 3, "{output:?}");
+        assert_eq!(lines[0], "INFO  app hello");
+        assert!(lines[1].starts_with("──── "), "{}", lines[1]);
+        assert!(lines[1].ends_with(" gap ────"), "{}", lines[1]);
+        assert_eq!(lines[2], "INFO  app hello");
+    }
+
+    #[test]
+    #[cfg(feature = "humantime")]
+    fn time_format() {
+        let line = render(Config {
+            with_system_timestamp: true,
+            time_format: Some("%Y-%m-%d %H:%M:%S%.f %z".to_owned()),
+            time_precision: Some(TimePrecision::Micros),
+            timezone: Some(Timezone::Fixed(-5 * 3600)),
+            ..Config::default()
+        });
+
+        // e.g. 2024-01-31 07:34:56.789012 -0500 INFO  app hello
+        let (time, rest) = line.split_at("2024-01-31 07:34:56.789012 -0500 ".len());
+        assert_eq!(&time[10..11], " ");
+        assert_eq!(&time[19..20], ".");
+        assert!(time.ends_with(" -0500 "), "{time}");
+        assert_eq!(rest, "INFO  app hello\n");
+    }
+
+    #[test]
+    fn thread_column_has_its_own_width() {
+        let capture = Capture::new(Config {
+            with_thread_name: true,
+            with_padding: true,
+            padding: PaddingStrategy::Fixed(30),
+            ..Config::default()
+        });
+        let output = thread::Builder::new()
+            .name("main".to_owned())
+            .spawn(move || {
+                capture.log(Level::Info, "hi");
+                capture.output()
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(output, format!("INFO  main app{:27} > hi\n", ""));
+    }
+
+    #[test]
+    #[cfg(all(feature = "journald", target_os = "linux"))]
+    fn journald_is_only_enabled_automatically_by_init() {
+        use std::os::fd::AsFd;
+        use std::os::unix::fs::MetadataExt;
+        use std::os::unix::net::UnixDatagram;
+
+        let path = env::temp_dir().join(format!("lovely-auto-journald-{}", std::process::id()));
+        let socket = UnixDatagram::bind(&path).unwrap();
+        socket.set_nonblocking(true).unwrap();
+        // Pretend that standard error is connected to the journal
+        let stderr = std::io::stderr().as_fd().try_clone_to_owned().unwrap();
+        let metadata = std::fs::File::from(stderr).metadata().unwrap();
+        env::set_var(
+            "JOURNAL_STREAM",
+            format!("{}:{}", metadata.dev(), metadata.ino()),
+        );
+        assert!(systemd::stderr_is_journal());
+
+        let capture = Capture::new(Config {
+            journald: Journald {
+                mode: JournaldMode::Auto,
+                socket: path.clone(),
+            },
+            ..Config::default()
+        });
+        capture.log(Level::Info, "hello");
+        assert_eq!(capture.output(), "INFO  app hello\n");
+        assert!(socket.recv(&mut [0; 1024]).is_err());
+
+        // As used by the init functions
+        let config = Config {
+            journald: Journald {
+                mode: JournaldMode::Auto,
+                socket: path.clone(),
+            },
+            ..Config::default()
+        };
+        let logger = build_formatted(config, None)
+            .filter_level(LevelFilter::Trace)
+            .build();
+        logger.log(
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .args(format_args!("hello"))
+                .build(),
+        );
+        env::remove_var("JOURNAL_STREAM");
+
+        let mut buf = [0; 1024];
+        let len = socket.recv(&mut buf).unwrap();
+        let fields = std::str::from_utf8(&buf[..len]).unwrap();
+        assert_eq!(fields, "PRIORITY=6\nMESSAGE=hello\nTARGET=app\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn sd_prefix_on_every_line() {
+        let capture = Capture::colored(Config {
+            sd_prefix: Some(true),
+            with_file_name: true,
+            hyperlinks: Some(true),
+            highlight_values: true,
+            color_targets: TargetColors::Target,
+            ..Config::default()
+        });
+        capture.log(Level::Warn, "first 42\nsecond");
+        capture.log(Level::Error, "third");
+        assert_eq!(
+            capture.output(),
+            "<4>WARN  app:src/main.rs first 42\n<4>second\n<3>ERROR app:src/main.rs third\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "reltime")]
+    fn sd_prefix_on_gap_markers() {
+        let capture = Capture::colored(Config {
+            sd_prefix: Some(true),
+            gap_marker: Some(Duration::ZERO),
+            ..Config::default()
+        });
+        capture.log(Level::Info, "hello");
+        capture.log(Level::Info, "hello");
+
+        let output = capture.output();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 3, "{output:?}");
+        assert_eq!(lines[0], "<6>INFO  app hello");
+        assert!(lines[1].starts_with("<6>──── "), "{output:?}");
+        assert!(lines[1].ends_with(" gap ────"), "{output:?}");
+        assert_eq!(lines[2], "<6>INFO  app hello");
+    }
+
+    #[test]
+    fn sd_prefix_once_per_json_object() {
+        let capture = Capture::colored(Config {
+            sd_prefix: Some(true),
+            output: Output::Json,
+            ..Config::default()
+        });
+        capture.log(Level::Info, "first\nsecond");
+
+        let output = capture.output();
+        assert!(output.starts_with("<6>{"), "{output:?}");
+        assert!(
+            output.ends_with("\"message\":\"first\\nsecond\"}\n"),
+            "{output:?}"
+        );
+        assert_eq!(output.matches("<6>").count(), 1);
+        assert_eq!(output.lines().count(), 1);
+    }
+
+    #[test]
+    fn unknown_color_targets_fall_back_to_config() {
+        let prefix = "LOVELY_TEST_COLOR_TARGETS";
+        env::set_var(format!("{prefix}_COLOR_TARGETS"), "rainbow");
+        let config = Config::from_environment_variables(
+            prefix,
+            Config {
+                color_targets: TargetColors::Crate,
+                ..Config::default()
+            },
+        );
+        assert_eq!(config.color_targets, TargetColors::Crate);
+
+        env::set_var(format!("{prefix}_COLOR_TARGETS"), "off");
+        let config = Config::from_environment_variables(
+            prefix,
+            Config {
+                color_targets: TargetColors::Crate,
+                ..Config::default()
+            },
+        );
+        assert_eq!(config.color_targets, TargetColors::Off);
+        env::remove_var(format!("{prefix}_COLOR_TARGETS"));
+    }
+
+    #[test]
+    fn hyperlinks() {
+        let capture = Capture::colored(Config {
+            with_file_name: true,
+            with_line_number: true,
+            hyperlinks: Some(true),
+            editor_url: Some("vscode://file/{path}:{line}".to_owned()),
+            ..Config::default()
+        });
+        capture.log(Level::Info, "hello");
+
+        let line = capture.output();
+        assert!(line.contains("\x1b]8;;vscode://file/"), "{line:?}");
+        assert!(
+            line.contains("/src/main.rs:42\x1b\\src/main.rs:42\x1b]8;;\x1b\\"),
+            "{line:?}"
+        );
+    }
 }
@@ -0,0 +1,139 @@
+//! logfmt output.
+
+use std::fmt::Display;
+use std::io::{self, Write};
+
+use env_logger::fmt::Formatter;
+
+use crate::{thread_id, Config};
+
+/// Writes a record as a logfmt line, such as
+/// `ts=2024-01-31T12:34:56.789Z level=info target=app msg="hello world"`
+pub(crate) fn write(f: &mut Formatter, record: &log::Record, config: &Config) -> io::Result<()> {
+    #[cfg(feature = "humantime")]
+    {
+        let timestamp = f.timestamp_millis();
+        write!(f, "ts={timestamp} ")?;
+    }
+    write!(f, "level={}", record.level().as_str().to_lowercase())?;
+    write!(f, " target=")?;
+    write_value(f, record.target())?;
+    if let Some(file) = record.file() {
+        write!(f, " file=")?;
+        write_value(f, file)?;
+    }
+    if let Some(line) = record.line() {
+        write!(f, " line={line}")?;
+    }
+    if config.with_thread_name || config.with_thread_id {
+        let current = std::thread::current();
+        if config.with_thread_name {
+            write!(f, " thread=")?;
+            write_value(f, current.name().unwrap_or("<unnamed>"))?;
+        }
+        if config.with_thread_id {
+            write!(f, " thread_id={}", thread_id(&current))?;
+        }
+    }
+    write!(f, " msg=")?;
+    write_value(f, record.args())?;
+    #[cfg(feature = "kv")]
+    for (key, value) in crate::kv::collect(record) {
+        write!(f, " ")?;
+        write_key(f, &key)?;
+        write!(f, "=")?;
+        write_value(f, value)?;
+    }
+    writeln!(f)
+}
+
+#[cfg(feature = "kv")]
+/// Writes a key, replacing the characters not allowed in keys by `_`
+fn write_key(out: &mut impl Write, key: &str) -> io::Result<()> {
+    let key: String = key
+        .chars()
+        .map(|c| {
+            if c <= ' ' || c == '=' || c == '"' || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    out.write_all(key.as_bytes())
+}
+
+/// Writes a value, quoted and escaped when it is empty or contains spaces,
+/// `=`, `"`, `\` or control characters
+fn write_value(out: &mut impl Write, value: impl Display) -> io::Result<()> {
+    let value = value.to_string();
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c <= ' ' || c == '=' || c == '"' || c == '\\' || c.is_control());
+    if !needs_quotes {
+        return out.write_all(value.as_bytes());
+    }
+    write!(out, "\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => write!(out, "\\\"")?,
+            '\\' => write!(out, "\\\\")?,
+            '\n' => write!(out, "\\n")?,
+            '\r' => write!(out, "\\r")?,
+            '\t' => write!(out, "\\t")?,
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{c}")?,
+        }
+    }
+    write!(out, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_value;
+
+    /// Returns a value written as a logfmt value
+    fn value(value: &str) -> String {
+        let mut out = Vec::new();
+        write_value(&mut out, value).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn plain_values_are_not_quoted() {
+        assert_eq!(value("hello"), "hello");
+        assert_eq!(value("app::db"), "app::db");
+        assert_eq!(value("héllo"), "héllo");
+    }
+
+    #[test]
+    fn values_are_quoted_and_escaped() {
+        assert_eq!(value(""), r#""""#);
+        assert_eq!(value("hello world"), r#""hello world""#);
+        assert_eq!(value("a=b"), r#""a=b""#);
+        assert_eq!(value(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(value(r"C:\temp"), r#""C:\\temp""#);
+        assert_eq!(value("a\nb"), r#""a\nb""#);
+        assert_eq!(value("a\r\tb"), r#""a\r\tb""#);
+        assert_eq!(value("\u{1}"), r#""\u0001""#);
+        assert_eq!(value("\u{7f}"), r#""\u007f""#);
+    }
+
+    #[test]
+    #[cfg(feature = "kv")]
+    fn keys_are_sanitised() {
+        let key = |key: &str| {
+            let mut out = Vec::new();
+            super::write_key(&mut out, key).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(key("user_id"), "user_id");
+        assert_eq!(key("user id"), "user_id");
+        assert_eq!(key("a=b"), "a_b");
+        assert_eq!(key(r#"a"b"#), "a_b");
+        assert_eq!(key("a\tb\n"), "a_b_");
+        assert_eq!(key("a\u{7f}"), "a_");
+        assert_eq!(key("clé"), "clé");
+    }
+}
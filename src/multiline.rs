@@ -0,0 +1,281 @@
+//! Display of messages spanning multiple lines, as set through
+//! `Config::multiline` or the `RUST_LOG_MULTILINE` environment variable.
+
+use std::io::{self, Write};
+
+use crate::highlight::Highlighted;
+use crate::wrap::wrap;
+
+/// How messages spanning multiple lines are displayed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Multiline {
+    /// Continuation lines start at the first column
+    #[default]
+    Off,
+    /// Continuation lines are aligned under the first line of the message
+    Indent {
+        /// Character displayed before continuation lines, if any
+        gutter: Option<char>,
+    },
+    /// Timestamps, level and target are repeated on every line
+    RepeatHeader,
+}
+
+impl Multiline {
+    /// Parses `indent`, `indent:<gutter>` or `repeat-header`
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(Self::Off),
+            "indent" => Some(Self::Indent { gutter: None }),
+            "repeat-header" => Some(Self::RepeatHeader),
+            _ => {
+                let gutter = value.strip_prefix("indent:")?;
+                let mut chars = gutter.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(Self::Indent { gutter: Some(c) }),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Writes a log line: the rendered fields before the message, the
+    /// message, and the rendered fields after it.
+    ///
+    /// When the width of the terminal is given, the lines of the message are
+    /// wrapped so that they fit in it, and the wrapped parts are aligned
+    /// under the first line of the message.
+    pub(crate) fn write(
+        self,
+        out: &mut impl Write,
+        prefix: &[u8],
+        suffix: &[u8],
+        message: &Highlighted,
+        terminal_width: Option<usize>,
+    ) -> io::Result<()> {
+        let width = visible_width(prefix);
+        let wrap_width = terminal_width
+            .and_then(|terminal_width| terminal_width.checked_sub(width))
+            .filter(|&wrap_width| wrap_width >= MIN_WRAP_WIDTH);
+        let mut lines = message.text().split('\n');
+        let first = lines.next().unwrap_or_default();
+        out.write_all(prefix)?;
+        write_wrapped(out, first, message, width, wrap_width)?;
+
+        match self {
+            Self::Off => {
+                for line in lines {
+                    out.write_all(b"\n")?;
+                    write_wrapped(out, line, message, width, wrap_width)?;
+                }
+            }
+            Self::Indent { gutter } => {
+                for line in lines {
+                    match gutter {
+                        Some(gutter) if width >= 2 => {
+                            write!(out, "\n{:width$}{gutter} ", "", width = width - 2)?
+                        }
+                        _ => write!(out, "\n{:width$}", "")?,
+                    }
+                    write_wrapped(out, line, message, width, wrap_width)?;
+                }
+            }
+            Self::RepeatHeader => {
+                for line in lines {
+                    out.write_all(b"\n")?;
+                    out.write_all(prefix)?;
+                    write_wrapped(out, line, message, width, wrap_width)?;
+                }
+            }
+        }
+        out.write_all(suffix)?;
+        out.write_all(b"\n")
+    }
+}
+
+/// Narrowest width messages are wrapped to. Messages are not wrapped when the
+/// fields before them leave less room than that.
+const MIN_WRAP_WIDTH: usize = 20;
+
+/// Writes a line of a message, wrapped to `wrap_width` characters if set, with
+/// the wrapped parts starting at the given column
+fn write_wrapped(
+    out: &mut impl Write,
+    line: &str,
+    message: &Highlighted,
+    column: usize,
+    wrap_width: Option<usize>,
+) -> io::Result<()> {
+    let pieces = match wrap_width {
+        Some(wrap_width) => wrap(line, wrap_width),
+        None => vec![line],
+    };
+    for (i, piece) in pieces.into_iter().enumerate() {
+        if i > 0 {
+            write!(out, "\n{:column$}", "")?;
+        }
+        message.write(out, piece)?;
+    }
+    Ok(())
+}
+
+/// Returns the number of characters displayed by some text, ignoring ANSI
+/// escape sequences
+pub(crate) fn visible_width(text: &[u8]) -> usize {
+    let mut width = 0;
+    let mut bytes = text.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte == 0x1b {
+            skip_escape(&mut bytes);
+        } else if byte & 0xc0 != 0x80 {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Returns some text without its ANSI escape sequences
+pub(crate) fn strip_escapes(text: &[u8]) -> Vec<u8> {
+    let mut stripped = Vec::with_capacity(text.len());
+    let mut bytes = text.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte == 0x1b {
+            skip_escape(&mut bytes);
+        } else {
+            stripped.push(byte);
+        }
+    }
+    stripped
+}
+
+/// Skips the rest of an escape sequence, after its ESC byte
+fn skip_escape(bytes: &mut std::slice::Iter<u8>) {
+    if bytes.as_slice().first() == Some(&b']') {
+        // Skip the operating system command, such as a hyperlink, up to BEL
+        // or ST
+        while let Some(&byte) = bytes.next() {
+            if byte == 0x07 || (byte == 0x1b && bytes.next() == Some(&b'\\')) {
+                break;
+            }
+        }
+    } else {
+        // Skip the escape sequence, up to its final byte
+        for &byte in bytes.by_ref() {
+            if (0x40..=0x7e).contains(&byte) && byte != b'[' {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use env_logger::fmt::style::Style;
+
+    use super::{strip_escapes, visible_width, Multiline};
+    use crate::highlight::Highlighted;
+
+    /// Returns a log line written with a multiline mode
+    fn render(multiline: Multiline, message: &str, terminal_width: Option<usize>) -> String {
+        let message = Highlighted::new(message, Style::new(), Vec::new());
+        let mut out = Vec::new();
+        multiline
+            .write(&mut out, b"INFO  app ", b" k=v", &message, terminal_width)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Multiline::parse("off"), Some(Multiline::Off));
+        assert_eq!(
+            Multiline::parse("indent"),
+            Some(Multiline::Indent { gutter: None })
+        );
+        assert_eq!(
+            Multiline::parse("indent:│"),
+            Some(Multiline::Indent {
+                gutter: Some('│')
+            })
+        );
+        assert_eq!(
+            Multiline::parse("repeat-header"),
+            Some(Multiline::RepeatHeader)
+        );
+        assert_eq!(Multiline::parse("indent:"), None);
+        assert_eq!(Multiline::parse("indent:||"), None);
+        assert_eq!(Multiline::parse("repeat"), None);
+    }
+
+    #[test]
+    fn off() {
+        assert_eq!(
+            render(Multiline::Off, "first\nsecond", None),
+            "INFO  app first\nsecond k=v\n"
+        );
+    }
+
+    #[test]
+    fn indent() {
+        assert_eq!(
+            render(Multiline::Indent { gutter: None }, "first\nsecond", None),
+            "INFO  app first\n          second k=v\n"
+        );
+        assert_eq!(
+            render(
+                Multiline::Indent {
+                    gutter: Some('│')
+                },
+                "first\nsecond\nthird",
+                None
+            ),
+            "INFO  app first\n        │ second\n        │ third k=v\n"
+        );
+    }
+
+    #[test]
+    fn repeat_header() {
+        assert_eq!(
+            render(Multiline::RepeatHeader, "first\nsecond", None),
+            "INFO  app first\nINFO  app second k=v\n"
+        );
+    }
+
+    #[test]
+    fn wrapped_parts_are_aligned() {
+        assert_eq!(
+            render(
+                Multiline::Off,
+                "the quick brown fox jumps over the lazy dog",
+                Some(40)
+            ),
+            "INFO  app the quick brown fox jumps over\n          the lazy dog k=v\n"
+        );
+    }
+
+    #[test]
+    fn visible_width_skips_escape_sequences() {
+        assert_eq!(visible_width(b"INFO  app "), 10);
+        assert_eq!(visible_width("│ é".as_bytes()), 3);
+        assert_eq!(visible_width(b"\x1b[1;31mERROR\x1b[0m"), 5);
+        // Hyperlinks, terminated by ST or by BEL
+        assert_eq!(
+            visible_width(b"\x1b]8;;file:///src/main.rs\x1b\\main.rs:42\x1b]8;;\x1b\\ "),
+            11
+        );
+        assert_eq!(
+            visible_width(b"\x1b]8;;file:///src/main.rs\x07main.rs:42\x1b]8;;\x07 "),
+            11
+        );
+    }
+
+    #[test]
+    fn escape_sequences_are_stripped() {
+        assert_eq!(
+            strip_escapes(
+                b"\x1b[1;31mERROR\x1b[0m \x1b]8;;file:///a.rs\x1b\\a.rs\x1b]8;;\x1b\\ \xe2\x94\x82"
+            ),
+            "ERROR a.rs │".as_bytes()
+        );
+    }
+}
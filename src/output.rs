@@ -0,0 +1,32 @@
+//! Output formats, as set through `Config::output` or the `RUST_LOG_OUTPUT`
+//! environment variable.
+
+/// Format of the log lines
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Output {
+    /// Lovely colored lines, laid out by `Config::format` and the other
+    /// options
+    #[default]
+    Pretty,
+    /// One JSON object per line, with the `timestamp`, `level`, `target`,
+    /// `file`, `line`, `message` fields, and the key-values of the log
+    Json,
+    /// One logfmt line per log, with the `ts`, `level`, `target`, `file`,
+    /// `line`, `msg` keys, and the key-values of the log
+    Logfmt,
+    /// Syslog messages, as set by `Config::syslog`
+    Syslog,
+}
+
+impl Output {
+    /// Parses `pretty`, `json`, `logfmt` or `syslog`
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pretty" => Some(Self::Pretty),
+            "json" => Some(Self::Json),
+            "logfmt" => Some(Self::Logfmt),
+            "syslog" => Some(Self::Syslog),
+            _ => None,
+        }
+    }
+}
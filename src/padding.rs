@@ -0,0 +1,262 @@
+//! Padding of the targets, as set through `Config::padding` or the
+//! `RUST_LOG_PADDING` environment variable.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::Config;
+
+/// Widest padding, well below the largest width supported by `format!()`
+pub(crate) const MAX_WIDTH: usize = 1024;
+
+/// How the width of the padded targets is computed, when padding is enabled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PaddingStrategy {
+    /// Pad to a fixed width, up to 1024. Longer targets are not padded.
+    Fixed(usize),
+    /// Pad to the width of the longest target seen so far
+    #[default]
+    GrowOnly,
+    /// Pad to the width of the longest target of the last given number of
+    /// lines
+    Rolling(usize),
+    /// Pad to the width of the longest target named in the filter
+    /// directives, then grow as [`PaddingStrategy::GrowOnly`].
+    ///
+    /// Directives are read from `RUST_LOG`, or from the environment variable
+    /// given to `init_custom_env()`.
+    FromFilters,
+}
+
+impl PaddingStrategy {
+    /// Parses `fixed:<width>`, `grow`, `rolling:<lines>` or `filters`
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.split_once(':') {
+            None if value == "grow" => Some(Self::GrowOnly),
+            None if value == "filters" => Some(Self::FromFilters),
+            Some(("fixed", width)) => width
+                .parse()
+                .ok()
+                .filter(|&width| width <= MAX_WIDTH)
+                .map(Self::Fixed),
+            Some(("rolling", lines)) => lines
+                .parse()
+                .ok()
+                .filter(|&lines| lines > 0)
+                .map(Self::Rolling),
+            _ => None,
+        }
+    }
+}
+
+/// Padding state of a logger
+pub(crate) struct Padding {
+    /// How the width is computed
+    strategy: PaddingStrategy,
+    /// Longest width seen so far, for the strategies growing
+    max_width: AtomicUsize,
+    /// Widths of the last lines, for the rolling strategy
+    recent: Mutex<VecDeque<usize>>,
+}
+
+impl Padding {
+    /// Creates the padding state of a logger, with the filter directives
+    /// used by this logger, if any
+    pub(crate) fn new(config: &Config, filters: Option<&str>) -> Self {
+        let initial_width = match (config.padding, filters) {
+            (PaddingStrategy::FromFilters, Some(filters)) => filter_targets(filters)
+                .map(|target| {
+                    config
+                        .target_shortening
+                        .shorten(target, config.max_target_width)
+                        .chars()
+                        .count()
+                })
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        };
+        Self {
+            strategy: config.padding,
+            max_width: AtomicUsize::new(initial_width),
+            recent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Creates the padding state of a column padded to the width of its
+    /// longest value seen so far, whatever the strategy of the targets
+    pub(crate) fn grow_only() -> Self {
+        Self {
+            strategy: PaddingStrategy::GrowOnly,
+            max_width: AtomicUsize::new(0),
+            recent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Returns the width to pad a target of the given width to, at most
+    /// `MAX_WIDTH`
+    pub(crate) fn width(&self, width: usize) -> usize {
+        let padded = match self.strategy {
+            PaddingStrategy::Fixed(fixed) => fixed.max(width),
+            PaddingStrategy::GrowOnly | PaddingStrategy::FromFilters => {
+                let previous = self.max_width.fetch_max(width, Ordering::Relaxed);
+                previous.max(width)
+            }
+            PaddingStrategy::Rolling(lines) => {
+                let mut recent = self.recent.lock().unwrap();
+                if recent.len() >= lines {
+                    recent.pop_front();
+                }
+                recent.push_back(width);
+                recent.iter().copied().max().unwrap_or(width)
+            }
+        };
+        padded.min(MAX_WIDTH)
+    }
+}
+
+/// Returns the targets named in filter directives such as
+/// `warn,my_app::db=debug,hyper/regex`
+fn filter_targets(filters: &str) -> impl Iterator<Item = &str> {
+    let directives = filters.split('/').next().unwrap_or_default();
+    directives.split(',').filter_map(|directive| {
+        let target = match directive.split_once('=') {
+            Some((target, _)) => target,
+            None if is_level(directive) => return None,
+            None => directive,
+        };
+        Some(target.trim()).filter(|target| !target.is_empty())
+    })
+}
+
+/// Whether a directive is a level, rather than a target
+fn is_level(directive: &str) -> bool {
+    ["off", "error", "warn", "info", "debug", "trace"]
+        .iter()
+        .any(|level| directive.trim().eq_ignore_ascii_case(level))
+}
+
+#[cfg(test)]
+mod tests {
+    use log::Level;
+
+    use super::{filter_targets, Padding, PaddingStrategy, MAX_WIDTH};
+    use crate::tests::Capture;
+    use crate::Config;
+
+    /// Returns the padding state of a strategy, with some filter directives
+    fn padding(strategy: PaddingStrategy, filters: Option<&str>) -> Padding {
+        let config = Config {
+            padding: strategy,
+            ..Config::default()
+        };
+        Padding::new(&config, filters)
+    }
+
+    /// Returns the widths the given widths are padded to, in order
+    fn widths(padding: &Padding, widths: &[usize]) -> Vec<usize> {
+        widths.iter().map(|&width| padding.width(width)).collect()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            PaddingStrategy::parse("fixed:30"),
+            Some(PaddingStrategy::Fixed(30))
+        );
+        assert_eq!(
+            PaddingStrategy::parse("grow"),
+            Some(PaddingStrategy::GrowOnly)
+        );
+        assert_eq!(
+            PaddingStrategy::parse("rolling:100"),
+            Some(PaddingStrategy::Rolling(100))
+        );
+        assert_eq!(
+            PaddingStrategy::parse("filters"),
+            Some(PaddingStrategy::FromFilters)
+        );
+        assert_eq!(PaddingStrategy::parse("rolling:0"), None);
+        assert_eq!(PaddingStrategy::parse("fixed"), None);
+        assert_eq!(PaddingStrategy::parse("fixed:70000"), None);
+    }
+
+    #[test]
+    fn fixed_is_a_lower_bound() {
+        let padding = padding(PaddingStrategy::Fixed(10), None);
+        assert_eq!(widths(&padding, &[3, 15, 3]), [10, 15, 10]);
+    }
+
+    #[test]
+    fn widths_are_bounded() {
+        let fixed = padding(PaddingStrategy::Fixed(70_000), None);
+        assert_eq!(widths(&fixed, &[3]), [MAX_WIDTH]);
+        let grow = padding(PaddingStrategy::GrowOnly, None);
+        assert_eq!(widths(&grow, &[70_000, 3]), [MAX_WIDTH, MAX_WIDTH]);
+
+        // Formatting widths above u16::MAX panic
+        let capture = Capture::new(Config {
+            with_padding: true,
+            padding: PaddingStrategy::Fixed(70_000),
+            ..Config::default()
+        });
+        capture.log(Level::Info, "hello");
+        assert!(capture.output().ends_with(" > hello\n"));
+
+        let capture = Capture::new(Config {
+            with_padding: true,
+            with_file_name: true,
+            ..Config::default()
+        });
+        capture.log_to(Level::Info, &"a".repeat(2 * MAX_WIDTH), "hello");
+        assert!(capture.output().ends_with(":src/main.rs > hello\n"));
+    }
+
+    #[test]
+    fn grow_only() {
+        let padding = padding(PaddingStrategy::GrowOnly, None);
+        assert_eq!(widths(&padding, &[5, 3, 8, 3]), [5, 5, 8, 8]);
+    }
+
+    #[test]
+    fn rolling_evicts_old_lines() {
+        let padding = padding(PaddingStrategy::Rolling(2), None);
+        assert_eq!(widths(&padding, &[10, 3, 4, 2, 2]), [10, 10, 4, 4, 2]);
+    }
+
+    #[test]
+    fn filter_directives() {
+        let targets: Vec<_> = filter_targets("warn,a::b=debug,x/regex").collect();
+        assert_eq!(targets, ["a::b", "x"]);
+        let targets: Vec<_> = filter_targets(" INFO , my_app = trace,,").collect();
+        assert_eq!(targets, ["my_app"]);
+        assert_eq!(filter_targets("").count(), 0);
+
+        let padding = padding(PaddingStrategy::FromFilters, Some("warn,my_app::db=debug"));
+        assert_eq!(widths(&padding, &[3, 12, 3]), [10, 12, 12]);
+    }
+
+    #[test]
+    fn builders_are_isolated() {
+        let first = padding(PaddingStrategy::GrowOnly, None);
+        let second = padding(PaddingStrategy::GrowOnly, None);
+        assert_eq!(first.width(20), 20);
+        assert_eq!(second.width(3), 3);
+
+        let config = || Config {
+            with_padding: true,
+            ..Config::default()
+        };
+        let first = Capture::new(config());
+        let second = Capture::new(config());
+        first.log_to(Level::Info, "app::a::long::module", "hello");
+        second.log(Level::Info, "hello");
+        first.log(Level::Info, "hello");
+        assert_eq!(second.output(), "INFO  app > hello\n");
+        assert_eq!(
+            first.output().lines().nth(1),
+            Some("INFO  app                  > hello")
+        );
+    }
+}
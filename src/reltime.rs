@@ -0,0 +1,255 @@
+//! Display of the relative timestamps, as set through `Config::reltime_style`
+//! and `Config::reltime_heat`, or the `RUST_LOG_RELTIME_STYLE` and
+//! `RUST_LOG_RELTIME_HEAT` environment variables, and of the gap markers and
+//! day banners.
+
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use env_logger::fmt::style::Style;
+
+use crate::Theme;
+
+/// Largest number of significant digits of the human style, as many as
+/// nanoseconds have below a second
+const MAX_PRECISION: usize = 9;
+
+/// How the differences displayed by relative timestamps are formatted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RelTimeStyle {
+    /// Seconds with nanoseconds, like dmesg, as `[  +1.234567890]`
+    #[default]
+    Dmesg,
+    /// The most readable unit among `ns`, `µs`, `ms` and `s`, as `[+123µs]`,
+    /// `[+4.2ms]` or `[ +1.7s]`
+    Human {
+        /// Number of significant digits, up to 9. Digits before the decimal
+        /// point are always displayed.
+        precision: usize,
+    },
+}
+
+impl RelTimeStyle {
+    /// Parses `dmesg`, `human` or `human:<precision>`, with a precision up to
+    /// 9
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "dmesg" => Some(Self::Dmesg),
+            "human" => Some(Self::Human { precision: 2 }),
+            _ => value
+                .strip_prefix("human:")?
+                .parse()
+                .ok()
+                .filter(|&precision| precision <= MAX_PRECISION)
+                .map(|precision| Self::Human { precision }),
+        }
+    }
+
+    /// Formats a difference between two logs.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use lovely_env_logger::RelTimeStyle;
+    ///
+    /// let dmesg = RelTimeStyle::Dmesg;
+    /// assert_eq!(dmesg.format(Duration::from_nanos(1_234_567_890)), "[  +1.234567890]");
+    /// let human = RelTimeStyle::Human { precision: 2 };
+    /// assert_eq!(human.format(Duration::from_micros(123)), "[+123µs]");
+    /// assert_eq!(human.format(Duration::from_micros(4_210)), "[+4.2ms]");
+    /// assert_eq!(human.format(Duration::from_millis(1_730)), "[ +1.7s]");
+    /// assert_eq!(human.format(Duration::from_nanos(850)), "[+850ns]");
+    /// assert_eq!(human.format(Duration::from_nanos(999_960)), "[+1.0ms]");
+    /// assert_eq!(human.format(Duration::from_nanos(9_999)), "[ +10µs]");
+    /// let human = RelTimeStyle::Human { precision: 4 };
+    /// assert_eq!(human.format(Duration::from_micros(4_210)), "[+4.210ms]");
+    /// ```
+    pub fn format(self, diff: Duration) -> String {
+        match self {
+            Self::Dmesg => {
+                let secs = format!("+{}", diff.as_secs());
+                format!("[{secs:>4}.{:0>9}]", diff.subsec_nanos())
+            }
+            Self::Human { precision } => {
+                let precision = precision.min(MAX_PRECISION);
+                let diff = format!("+{}", human(diff, precision));
+                // Wide enough for any value below 1000 of its unit
+                let width = (precision + 1).max(3) + 3;
+                format!("[{diff:>width$}]")
+            }
+        }
+    }
+}
+
+/// Units of the differences, with their number of nanoseconds
+const UNITS: [(&str, f64); 4] = [("ns", 1.0), ("µs", 1e3), ("ms", 1e6), ("s", 1e9)];
+
+/// Formats a duration in the most readable unit among `ns`, `µs`, `ms` and
+/// `s`, with the given number of significant digits, as `4.2ms`
+pub(crate) fn human(diff: Duration, precision: usize) -> String {
+    let nanos = diff.as_nanos() as f64;
+    let integer_digits = |value: f64| (value as u64).to_string().len();
+    let mut unit = UNITS
+        .iter()
+        .rposition(|&(_, scale)| nanos >= scale)
+        .unwrap_or(0);
+    loop {
+        let (name, scale) = UNITS[unit];
+        let decimals = match name {
+            "ns" => 0,
+            _ => precision.saturating_sub(integer_digits(nanos / scale)),
+        };
+        let factor = 10f64.powi(decimals as i32);
+        let value = (nanos / scale * factor).round() / factor;
+        // Rounding up may reach the next unit, as 999.96µs to 1.0ms
+        if value >= 1e3 && unit + 1 < UNITS.len() {
+            unit += 1;
+            continue;
+        }
+        // or add an integer digit, as 9.999µs to 10µs, leaving fewer decimals
+        let decimals = decimals.min(precision.saturating_sub(integer_digits(value)));
+        return format!("{value:.decimals$}{name}");
+    }
+}
+
+/// Returns the style of a difference on a heat scale: the style of the
+/// timestamps below 10ms, then the colors of the info, warn and error levels
+/// from 10ms, 100ms and 1s
+pub(crate) fn heat(theme: &Theme, diff: Duration) -> Style {
+    let color = match diff.as_millis() {
+        0..10 => return theme.timestamp,
+        10..100 => theme.info.get_fg_color(),
+        100..1_000 => theme.warn.get_fg_color(),
+        _ => theme.error.get_fg_color(),
+    };
+    theme.timestamp.fg_color(color)
+}
+
+/// Returns the line marking a gap between two logs, as `──── 2.3s gap ────`
+pub(crate) fn gap_marker(diff: Duration) -> String {
+    format!("──── {} gap ────", human(diff, 2))
+}
+
+/// Tracks when the date was last displayed by relative timestamps, to display
+/// it again periodically, as set through `Config::reltime_anchor_lines`,
+/// `Config::reltime_anchor_interval` and `Config::reltime_day_banner`
+pub(crate) struct Anchor {
+    /// Instant of the last date displayed, if any
+    time: Option<Instant>,
+    /// Number of logs since the last date displayed, including it
+    lines: usize,
+    /// Day of the previous log, when days are tracked
+    day: Option<NaiveDate>,
+}
+
+impl Anchor {
+    /// Creates an anchor for a logger which has not displayed any date yet
+    pub(crate) const fn new() -> Self {
+        Self {
+            time: None,
+            lines: 0,
+            day: None,
+        }
+    }
+
+    /// Records the day of a log, and returns it when it differs from the day
+    /// of the previous log
+    pub(crate) fn new_day(&mut self, day: Option<NaiveDate>) -> Option<NaiveDate> {
+        match (std::mem::replace(&mut self.day, day), day) {
+            (Some(previous), Some(day)) if previous != day => Some(day),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the date must be displayed again, after the given
+    /// number of logs or duration. The first log always displays the date
+    /// when either is set.
+    pub(crate) fn is_due(&self, lines: Option<usize>, interval: Option<Duration>) -> bool {
+        if lines.is_none() && interval.is_none() {
+            return false;
+        }
+        let Some(time) = self.time else {
+            return true;
+        };
+        lines.is_some_and(|lines| self.lines >= lines)
+            || interval.is_some_and(|interval| time.elapsed() >= interval)
+    }
+
+    /// Records that a log displayed the date
+    pub(crate) fn displayed(&mut self) {
+        self.time = Some(Instant::now());
+        self.lines = 1;
+    }
+
+    /// Records that a log displayed a difference
+    pub(crate) fn skipped(&mut self) {
+        self.lines += 1;
+    }
+}
+
+/// Returns the line announcing a new day, as `──── Fri 2026-10-16 ────`
+pub(crate) fn day_banner(day: NaiveDate) -> String {
+    format!("──── {} ────", day.format("%a %Y-%m-%d"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RelTimeStyle;
+    use crate::Theme;
+
+    #[test]
+    fn parse() {
+        assert_eq!(RelTimeStyle::parse("dmesg"), Some(RelTimeStyle::Dmesg));
+        assert_eq!(
+            RelTimeStyle::parse("human"),
+            Some(RelTimeStyle::Human { precision: 2 })
+        );
+        assert_eq!(
+            RelTimeStyle::parse("human:4"),
+            Some(RelTimeStyle::Human { precision: 4 })
+        );
+        assert_eq!(
+            RelTimeStyle::parse("human:9"),
+            Some(RelTimeStyle::Human { precision: 9 })
+        );
+        assert_eq!(RelTimeStyle::parse("human:10"), None);
+        assert_eq!(RelTimeStyle::parse("human:70000"), None);
+        assert_eq!(RelTimeStyle::parse("human:"), None);
+        assert_eq!(RelTimeStyle::parse("Dmesg"), None);
+    }
+
+    #[test]
+    fn precision_is_saturated() {
+        let diff = Duration::from_nanos(4_784);
+        let style = |precision| RelTimeStyle::Human { precision }.format(diff);
+        assert_eq!(style(9), "[+4.78400000µs]");
+        assert_eq!(style(30), style(9));
+        assert_eq!(style(70_000), style(9));
+    }
+
+    #[test]
+    fn gap_marker() {
+        assert_eq!(
+            super::gap_marker(Duration::from_millis(2300)),
+            "──── 2.3s gap ────"
+        );
+        assert_eq!(
+            super::gap_marker(Duration::from_micros(999_960)),
+            "──── 1.0s gap ────"
+        );
+    }
+
+    #[test]
+    fn heat() {
+        let theme = Theme::default();
+        let heat = |millis| super::heat(&theme, Duration::from_millis(millis));
+        assert_eq!(heat(9), theme.timestamp);
+        let color = |color| theme.timestamp.fg_color(color);
+        assert_eq!(heat(10), color(theme.info.get_fg_color()));
+        assert_eq!(heat(99), color(theme.info.get_fg_color()));
+        assert_eq!(heat(100), color(theme.warn.get_fg_color()));
+        assert_eq!(heat(1_000), color(theme.error.get_fg_color()));
+    }
+}
@@ -0,0 +1,110 @@
+//! Shortening of long targets, as set through `Config::target_shortening` and
+//! `Config::max_target_width`, or the `RUST_LOG_TARGET_SHORTENING` and
+//! `RUST_LOG_MAX_TARGET_WIDTH` environment variables.
+
+use std::borrow::Cow;
+
+/// How targets longer than the maximum width are shortened
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TargetShortening {
+    /// Cut the end of the target
+    #[default]
+    Truncate,
+    /// Abbreviate the leading segments of the target to their first letter,
+    /// from left to right, as `m::s::b::invoices::renderer`. The last segment
+    /// is kept as is. Every leading segment is abbreviated when there is no
+    /// maximum width.
+    Abbreviate,
+    /// Replace the middle of the target by an ellipsis, as
+    /// `my_app::serv…ices::renderer`
+    Elide,
+}
+
+impl TargetShortening {
+    /// Parses `truncate`, `abbreviate` or `elide`
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "truncate" => Some(Self::Truncate),
+            "abbreviate" => Some(Self::Abbreviate),
+            "elide" => Some(Self::Elide),
+            _ => None,
+        }
+    }
+
+    /// Shortens a target so that it fits in `max_width` characters, if set.
+    ///
+    /// Targets are cut when they are still too long once shortened.
+    ///
+    /// ```
+    /// use lovely_env_logger::TargetShortening;
+    ///
+    /// let target = "my_app::services::billing::invoices::renderer";
+    /// assert_eq!(
+    ///     TargetShortening::Abbreviate.shorten(target, None),
+    ///     "m::s::b::i::renderer"
+    /// );
+    /// assert_eq!(
+    ///     TargetShortening::Abbreviate.shorten(target, Some(30)),
+    ///     "m::s::b::invoices::renderer"
+    /// );
+    /// assert_eq!(
+    ///     TargetShortening::Elide.shorten(target, Some(21)),
+    ///     "my_app::se…::renderer"
+    /// );
+    /// assert_eq!(
+    ///     TargetShortening::Truncate.shorten(target, Some(16)),
+    ///     "my_app::services"
+    /// );
+    /// ```
+    pub fn shorten(self, target: &str, max_width: Option<usize>) -> Cow<'_, str> {
+        let len = target.chars().count();
+        if max_width.is_none_or(|max_width| len <= max_width) && self != Self::Abbreviate {
+            return Cow::Borrowed(target);
+        }
+
+        let shortened = match self {
+            Self::Truncate => Cow::Borrowed(target),
+            Self::Abbreviate => abbreviate(target, max_width),
+            Self::Elide => {
+                let max_width = max_width.unwrap_or(len);
+                if max_width == 0 {
+                    return Cow::Borrowed("");
+                }
+                let tail = (max_width - 1) / 2;
+                let head = max_width - 1 - tail;
+                let mut elided: String = target.chars().take(head).collect();
+                elided.push('…');
+                elided.extend(target.chars().skip(len - tail));
+                Cow::Owned(elided)
+            }
+        };
+        match max_width {
+            Some(max_width) if shortened.chars().count() > max_width => {
+                Cow::Owned(shortened.chars().take(max_width).collect())
+            }
+            _ => shortened,
+        }
+    }
+}
+
+/// Abbreviates the leading segments of a target until it fits in
+/// `max_width` characters
+fn abbreviate(target: &str, max_width: Option<usize>) -> Cow<'_, str> {
+    let mut segments: Vec<&str> = target.split("::").collect();
+    let mut len = target.chars().count();
+    let last = segments.len() - 1;
+    for segment in &mut segments[..last] {
+        if max_width.is_some_and(|max_width| len <= max_width) {
+            break;
+        }
+        if let Some(first) = segment.chars().next() {
+            len -= segment.chars().count() - 1;
+            *segment = &segment[..first.len_utf8()];
+        }
+    }
+    if len == target.chars().count() {
+        Cow::Borrowed(target)
+    } else {
+        Cow::Owned(segments.join("::"))
+    }
+}
@@ -0,0 +1,369 @@
+//! Syslog output, as set through `Config::syslog` or the `RUST_LOG_SYSLOG_*`
+//! environment variables.
+
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+
+use env_logger::fmt::Formatter;
+use log::Level;
+
+/// Format of the syslog messages
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SyslogFormat {
+    /// RFC 5424: `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID SD MSG`
+    #[default]
+    Rfc5424,
+    /// RFC 3164, the BSD syslog format: `<PRI>TIMESTAMP HOSTNAME TAG[PID]: MSG`.
+    ///
+    /// The timestamp requires to be compiled with the `humantime` or `reltime`
+    /// feature.
+    Rfc3164,
+}
+
+impl SyslogFormat {
+    /// Parses `rfc5424` or `rfc3164`
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "rfc5424" => Some(Self::Rfc5424),
+            "rfc3164" => Some(Self::Rfc3164),
+            _ => None,
+        }
+    }
+}
+
+/// Syslog facilities
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Facility {
+    /// Kernel messages
+    Kern = 0,
+    #[default]
+    /// User-level messages
+    User = 1,
+    /// Mail system
+    Mail = 2,
+    /// System daemons
+    Daemon = 3,
+    /// Security and authorization messages
+    Auth = 4,
+    /// Messages generated by the syslog daemon
+    Syslog = 5,
+    /// Line printer subsystem
+    Lpr = 6,
+    /// Network news subsystem
+    News = 7,
+    /// UUCP subsystem
+    Uucp = 8,
+    /// Clock daemon
+    Cron = 9,
+    /// Private security and authorization messages
+    Authpriv = 10,
+    /// FTP daemon
+    Ftp = 11,
+    /// Local use 0
+    Local0 = 16,
+    /// Local use 1
+    Local1 = 17,
+    /// Local use 2
+    Local2 = 18,
+    /// Local use 3
+    Local3 = 19,
+    /// Local use 4
+    Local4 = 20,
+    /// Local use 5
+    Local5 = 21,
+    /// Local use 6
+    Local6 = 22,
+    /// Local use 7
+    Local7 = 23,
+}
+
+impl Facility {
+    /// Parses the name of a facility, such as `daemon` or `local0`
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "kern" => Some(Self::Kern),
+            "user" => Some(Self::User),
+            "mail" => Some(Self::Mail),
+            "daemon" => Some(Self::Daemon),
+            "auth" => Some(Self::Auth),
+            "syslog" => Some(Self::Syslog),
+            "lpr" => Some(Self::Lpr),
+            "news" => Some(Self::News),
+            "uucp" => Some(Self::Uucp),
+            "cron" => Some(Self::Cron),
+            "authpriv" => Some(Self::Authpriv),
+            "ftp" => Some(Self::Ftp),
+            "local0" => Some(Self::Local0),
+            "local1" => Some(Self::Local1),
+            "local2" => Some(Self::Local2),
+            "local3" => Some(Self::Local3),
+            "local4" => Some(Self::Local4),
+            "local5" => Some(Self::Local5),
+            "local6" => Some(Self::Local6),
+            "local7" => Some(Self::Local7),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the syslog severity of a level
+#[inline]
+pub(crate) fn severity(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// Configuration of the syslog output
+///
+/// The messages are sent to a Unix datagram socket when one is set, and
+/// written to the usual target otherwise:
+///
+/// ```
+/// # #[cfg(unix)]
+/// # fn main() {
+/// use std::os::unix::net::UnixDatagram;
+///
+/// use log::Log;
+/// use lovely_env_logger::{Config, Facility, Output, Syslog};
+///
+/// let path = std::env::temp_dir().join(format!("lovely-syslog-{}", std::process::id()));
+/// let socket = UnixDatagram::bind(&path).unwrap();
+///
+/// let logger = lovely_env_logger::formatted_builder(Config {
+///     output: Output::Syslog,
+///     syslog: Syslog {
+///         facility: Facility::Daemon,
+///         app_name: Some("app".to_owned()),
+///         hostname: Some("host".to_owned()),
+///         socket: Some(path.clone()),
+///         ..Syslog::default()
+///     },
+///     ..Config::default()
+/// })
+/// .filter_level(log::LevelFilter::Info)
+/// .build();
+/// logger.log(
+///     &log::Record::builder()
+///         .level(log::Level::Warn)
+///         .target("app::db")
+///         .args(format_args!("connection lost"))
+///         .build(),
+/// );
+///
+/// let mut buf = [0; 1024];
+/// let len = socket.recv(&mut buf).unwrap();
+/// let message = std::str::from_utf8(&buf[..len]).unwrap();
+/// // daemon (3) * 8 + warning (4)
+/// assert!(message.starts_with("<28>1 "));
+/// let pid = std::process::id();
+/// assert!(message.ends_with(&format!(" host app {pid} - - app::db: connection lost")));
+/// std::fs::remove_file(&path).unwrap();
+/// # }
+/// # #[cfg(not(unix))]
+/// # fn main() {}
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Syslog {
+    /// Format of the messages
+    pub format: SyslogFormat,
+    /// Facility of the messages
+    pub facility: Facility,
+    /// Name of the application, the name of the executable by default
+    pub app_name: Option<String>,
+    /// Name of the host, read from `/proc/sys/kernel/hostname` by default
+    pub hostname: Option<String>,
+    /// Path of a Unix datagram socket to send the messages to, such as
+    /// `/dev/log`
+    pub socket: Option<PathBuf>,
+}
+
+impl Syslog {
+    /// Returns the header fields of the messages, computed once
+    pub(crate) fn header(&self) -> SyslogHeader {
+        let app_name = self.app_name.clone().or_else(|| {
+            std::env::current_exe()
+                .ok()?
+                .file_name()?
+                .to_str()
+                .map(str::to_owned)
+        });
+        let hostname = self.hostname.clone().or_else(crate::hostname);
+        SyslogHeader {
+            format: self.format,
+            facility: self.facility,
+            app_name,
+            hostname,
+            pid: std::process::id(),
+        }
+    }
+}
+
+/// Header fields of the syslog messages
+pub(crate) struct SyslogHeader {
+    format: SyslogFormat,
+    facility: Facility,
+    app_name: Option<String>,
+    hostname: Option<String>,
+    pid: u32,
+}
+
+impl SyslogHeader {
+    /// Writes a record as a syslog message
+    pub(crate) fn write(&self, f: &mut Formatter, record: &log::Record) -> io::Result<()> {
+        let pri = self.facility as u8 * 8 + severity(record.level());
+        match self.format {
+            SyslogFormat::Rfc5424 => {
+                write!(f, "<{pri}>1 ")?;
+                #[cfg(feature = "humantime")]
+                {
+                    let timestamp = f.timestamp_millis();
+                    write!(f, "{timestamp}")?;
+                }
+                #[cfg(not(feature = "humantime"))]
+                write!(f, "-")?;
+                write!(
+                    f,
+                    " {} {} {} - - ",
+                    self.hostname.as_deref().unwrap_or("-"),
+                    self.app_name.as_deref().unwrap_or("-"),
+                    self.pid
+                )?;
+            }
+            SyslogFormat::Rfc3164 => {
+                write!(f, "<{pri}>")?;
+                #[cfg(any(feature = "humantime", feature = "reltime"))]
+                write!(f, "{} ", chrono::Local::now().format("%b %e %T"))?;
+                if let Some(ref hostname) = self.hostname {
+                    write!(f, "{hostname} ")?;
+                }
+                write!(
+                    f,
+                    "{}[{}]: ",
+                    self.app_name.as_deref().unwrap_or("-"),
+                    self.pid
+                )?;
+            }
+        }
+        write!(f, "{}: {}", record.target(), record.args())?;
+        #[cfg(feature = "kv")]
+        for (key, value) in crate::kv::collect(record) {
+            write!(f, " {key}={}", crate::kv::Quoted(&value))?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Unix datagram socket receiving syslog messages, one per datagram
+#[cfg(unix)]
+pub(crate) struct SyslogSocket {
+    socket: UnixDatagram,
+    path: PathBuf,
+}
+
+#[cfg(unix)]
+impl SyslogSocket {
+    /// Connects to the socket at the given path
+    pub(crate) fn connect(path: PathBuf) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(&path)?;
+        Ok(Self { socket, path })
+    }
+}
+
+#[cfg(unix)]
+impl Write for SyslogSocket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let message = buf.strip_suffix(b"\n").unwrap_or(buf);
+        if self.socket.send(message).is_err() {
+            // The syslog daemon may have been restarted
+            *self = Self::connect(self.path.clone())?;
+            self.socket.send(message)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Facility;
+
+    #[test]
+    fn facilities() {
+        assert_eq!(Facility::parse("kern"), Some(Facility::Kern));
+        assert_eq!(Facility::parse("daemon"), Some(Facility::Daemon));
+        assert_eq!(Facility::parse("authpriv"), Some(Facility::Authpriv));
+        assert_eq!(Facility::parse("local0"), Some(Facility::Local0));
+        assert_eq!(Facility::parse("local7"), Some(Facility::Local7));
+        assert_eq!(Facility::parse("local8"), None);
+        assert_eq!(Facility::parse("Daemon"), None);
+        assert_eq!(Facility::Local7 as u8, 23);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn rfc3164() {
+        use std::os::unix::net::UnixDatagram;
+
+        use log::Log;
+
+        use super::{Syslog, SyslogFormat};
+        use crate::{Config, Output};
+
+        let path = std::env::temp_dir().join(format!("lovely-rfc3164-{}", std::process::id()));
+        let socket = UnixDatagram::bind(&path).unwrap();
+        let logger = crate::formatted_builder(Config {
+            output: Output::Syslog,
+            syslog: Syslog {
+                format: SyslogFormat::Rfc3164,
+                facility: Facility::Local0,
+                app_name: Some("app".to_owned()),
+                hostname: Some("host".to_owned()),
+                socket: Some(path.clone()),
+            },
+            sd_prefix: Some(false),
+            ..Config::default()
+        })
+        .filter_level(log::LevelFilter::Info)
+        .build();
+        logger.log(
+            &log::Record::builder()
+                .level(log::Level::Warn)
+                .target("app::db")
+                .args(format_args!("connection lost"))
+                .build(),
+        );
+
+        let mut buf = [0; 1024];
+        let len = socket.recv(&mut buf).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let message = std::str::from_utf8(&buf[..len]).unwrap();
+        // local0 (16) * 8 + warning (4)
+        let message = message.strip_prefix("<132>").unwrap();
+        // e.g. Oct  6 20:31:08
+        #[cfg(any(feature = "humantime", feature = "reltime"))]
+        let message = {
+            let (timestamp, message) = message.split_at("Oct  6 20:31:08 ".len());
+            assert!(
+                chrono::NaiveDateTime::parse_from_str(&format!("2024 {timestamp}"), "%Y %b %e %T ")
+                    .is_ok(),
+                "{timestamp}"
+            );
+            message
+        };
+        let pid = std::process::id();
+        assert_eq!(
+            message,
+            format!("host app[{pid}]: app::db: connection lost")
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! Detection of the systemd journal.
+
+/// Whether standard error is connected to the systemd journal, as told by the
+/// `JOURNAL_STREAM` environment variable set by systemd
+#[cfg(unix)]
+pub(crate) fn stderr_is_journal() -> bool {
+    use std::fs::File;
+    use std::os::fd::AsFd;
+    use std::os::unix::fs::MetadataExt;
+
+    let Some(stream) = std::env::var_os("JOURNAL_STREAM") else {
+        return false;
+    };
+    // `JOURNAL_STREAM` holds the device and inode numbers of the stream
+    let Some((dev, ino)) = stream.to_str().and_then(|stream| stream.split_once(':')) else {
+        return false;
+    };
+    let (Ok(dev), Ok(ino)) = (dev.parse::<u64>(), ino.parse::<u64>()) else {
+        return false;
+    };
+    std::io::stderr()
+        .as_fd()
+        .try_clone_to_owned()
+        .and_then(|fd| File::from(fd).metadata())
+        .is_ok_and(|metadata| metadata.dev() == dev && metadata.ino() == ino)
+}
+
+/// Whether standard error is connected to the systemd journal
+#[cfg(not(unix))]
+pub(crate) fn stderr_is_journal() -> bool {
+    false
+}
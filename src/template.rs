@@ -0,0 +1,157 @@
+//! Line templates, as set through `Config::format` or the `RUST_LOG_FORMAT`
+//! environment variable.
+
+/// A field of a line template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Field {
+    /// Text copied as is
+    Literal(String),
+    /// `{time}`: the timestamp, when enabled
+    Time,
+    /// `{level}`: the log level
+    Level,
+    /// `{thread}`: the thread name and id, when enabled
+    Thread,
+    /// `{target}`: the target of the log, usually the module path
+    Target,
+    /// `{location}`: the file and line calling the log macro, when enabled
+    Location,
+    /// `{msg}`: the log message
+    Message,
+    /// `{kv}`: the key-values of the log, each preceded by a space
+    Kv,
+}
+
+/// A line template, parsed once when building the logger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Template {
+    fields: Vec<Field>,
+}
+
+impl Template {
+    /// Parses a template such as `"{time} {level} {target}{location} > {msg}"`.
+    ///
+    /// `{{` and `}}` are written as `{` and `}`. Unknown placeholders are kept
+    /// as literal text.
+    pub(crate) fn parse(template: &str) -> Self {
+        let mut fields = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+
+        while let Some(pos) = rest.find(['{', '}']) {
+            literal.push_str(&rest[..pos]);
+            let tail = &rest[pos..];
+            if tail.starts_with("{{") || tail.starts_with("}}") {
+                literal.push_str(&tail[..1]);
+                rest = &tail[2..];
+                continue;
+            }
+            let field = tail
+                .find('}')
+                .filter(|_| tail.starts_with('{'))
+                .and_then(|end| Self::placeholder(&tail[1..end]).map(|f| (f, end)));
+            match field {
+                Some((field, end)) => {
+                    if !literal.is_empty() {
+                        fields.push(Field::Literal(std::mem::take(&mut literal)));
+                    }
+                    fields.push(field);
+                    rest = &tail[end + 1..];
+                }
+                None => {
+                    literal.push_str(&tail[..1]);
+                    rest = &tail[1..];
+                }
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            fields.push(Field::Literal(literal));
+        }
+        Self { fields }
+    }
+
+    /// Returns the field matching a placeholder name
+    fn placeholder(name: &str) -> Option<Field> {
+        match name {
+            "time" => Some(Field::Time),
+            "level" => Some(Field::Level),
+            "thread" => Some(Field::Thread),
+            "target" => Some(Field::Target),
+            "location" => Some(Field::Location),
+            "msg" => Some(Field::Message),
+            "kv" => Some(Field::Kv),
+            _ => None,
+        }
+    }
+
+    /// The fields of the template, in order
+    #[inline]
+    pub(crate) fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Field, Template};
+
+    /// Returns a literal field
+    fn literal(text: &str) -> Field {
+        Field::Literal(text.to_owned())
+    }
+
+    #[test]
+    fn placeholders() {
+        let template = Template::parse("{time} {level} {target}{location} > {msg}");
+        assert_eq!(
+            template.fields(),
+            [
+                Field::Time,
+                literal(" "),
+                Field::Level,
+                literal(" "),
+                Field::Target,
+                Field::Location,
+                literal(" > "),
+                Field::Message,
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_braces() {
+        assert_eq!(Template::parse("{{level}}").fields(), [literal("{level}")]);
+        assert_eq!(
+            Template::parse("}} {{{msg}}}").fields(),
+            [literal("} {"), Field::Message, literal("}")]
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_are_literals() {
+        assert_eq!(
+            Template::parse("{foo} {level}").fields(),
+            [literal("{foo} "), Field::Level]
+        );
+        assert_eq!(Template::parse("{}").fields(), [literal("{}")]);
+    }
+
+    #[test]
+    fn unterminated_placeholder() {
+        assert_eq!(Template::parse("{level").fields(), [literal("{level")]);
+        assert_eq!(
+            Template::parse("{msg} {level").fields(),
+            [Field::Message, literal(" {level")]
+        );
+        assert_eq!(Template::parse("a}b").fields(), [literal("a}b")]);
+    }
+
+    #[test]
+    fn kv_before_msg() {
+        assert_eq!(
+            Template::parse("{kv} {msg}").fields(),
+            [Field::Kv, literal(" "), Field::Message]
+        );
+    }
+}
@@ -0,0 +1,480 @@
+//! Color themes, as set through `Config::theme`, or the `RUST_LOG_THEME` and
+//! `RUST_LOG_COLORS` environment variables.
+
+use env_logger::fmt::style::{Ansi256Color, AnsiColor, Color, RgbColor, Style};
+use log::Level;
+
+/// Styles used to display the different parts of a log line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// Style of the `TRACE` level
+    pub trace: Style,
+    /// Style of the `DEBUG` level
+    pub debug: Style,
+    /// Style of the `INFO` level
+    pub info: Style,
+    /// Style of the `WARN` level
+    pub warn: Style,
+    /// Style of the `ERROR` level
+    pub error: Style,
+    /// Style of the target of the log, usually the module path
+    pub target: Style,
+    /// Style of the file name and line number
+    pub location: Style,
+    /// Style of the timestamps
+    pub timestamp: Style,
+    /// Style of the log message
+    pub message: Style,
+    /// Style of the keys of the key-values
+    pub kv_key: Style,
+    /// Style of the values of the key-values
+    pub kv_value: Style,
+    /// Style of the numbers highlighted in messages
+    pub number: Style,
+    /// Style of the durations highlighted in messages, such as `1.5s`
+    pub duration: Style,
+    /// Style of the quoted strings highlighted in messages
+    pub string: Style,
+    /// Style of the file paths highlighted in messages
+    pub path: Style,
+    /// Style of the IP addresses highlighted in messages
+    pub address: Style,
+    /// Style of the URLs highlighted in messages
+    pub url: Style,
+}
+
+impl Default for Theme {
+    /// The default theme, meant for dark backgrounds
+    #[inline]
+    fn default() -> Self {
+        Self {
+            trace: Style::new()
+                .bold()
+                .fg_color(Some(Color::Ansi(AnsiColor::Magenta))),
+            debug: Style::new().fg_color(Some(Color::Ansi(AnsiColor::Blue))),
+            info: Style::new().fg_color(Some(Color::Ansi(AnsiColor::Green))),
+            warn: Style::new().fg_color(Some(Color::Ansi(AnsiColor::Yellow))),
+            error: Style::new()
+                .bold()
+                .fg_color(Some(Color::Ansi(AnsiColor::Red))),
+            target: Style::new().bold(),
+            location: Style::new(),
+            timestamp: Style::new(),
+            message: Style::new(),
+            kv_key: Style::new().fg_color(Some(Color::Ansi(AnsiColor::Cyan))),
+            kv_value: Style::new(),
+            number: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightBlue))),
+            duration: Style::new().fg_color(Some(Color::Ansi(AnsiColor::Magenta))),
+            string: Style::new().fg_color(Some(Color::Ansi(AnsiColor::Green))),
+            path: Style::new().fg_color(Some(Color::Ansi(AnsiColor::Cyan))),
+            address: Style::new().fg_color(Some(Color::Ansi(AnsiColor::Yellow))),
+            url: Style::new()
+                .underline()
+                .fg_color(Some(Color::Ansi(AnsiColor::Cyan))),
+        }
+    }
+}
+
+/// Colors of the solarized palette
+mod solarized {
+    use env_logger::fmt::style::{Color, RgbColor};
+
+    pub(super) const BASE01: Color = Color::Rgb(RgbColor(0x58, 0x6e, 0x75));
+    pub(super) const YELLOW: Color = Color::Rgb(RgbColor(0xb5, 0x89, 0x00));
+    pub(super) const RED: Color = Color::Rgb(RgbColor(0xdc, 0x32, 0x2f));
+    pub(super) const VIOLET: Color = Color::Rgb(RgbColor(0x6c, 0x71, 0xc4));
+    pub(super) const BLUE: Color = Color::Rgb(RgbColor(0x26, 0x8b, 0xd2));
+    pub(super) const CYAN: Color = Color::Rgb(RgbColor(0x2a, 0xa1, 0x98));
+    pub(super) const GREEN: Color = Color::Rgb(RgbColor(0x85, 0x99, 0x00));
+    pub(super) const MAGENTA: Color = Color::Rgb(RgbColor(0xd3, 0x36, 0x82));
+    pub(super) const ORANGE: Color = Color::Rgb(RgbColor(0xcb, 0x4b, 0x16));
+}
+
+impl Theme {
+    /// A theme using the solarized palette, readable on both light and dark
+    /// backgrounds
+    pub fn solarized() -> Self {
+        Self {
+            trace: Style::new().fg_color(Some(solarized::VIOLET)),
+            debug: Style::new().fg_color(Some(solarized::CYAN)),
+            info: Style::new().fg_color(Some(solarized::GREEN)),
+            warn: Style::new().bold().fg_color(Some(solarized::YELLOW)),
+            error: Style::new().bold().fg_color(Some(solarized::RED)),
+            target: Style::new().fg_color(Some(solarized::BLUE)),
+            location: Style::new().fg_color(Some(solarized::BASE01)),
+            timestamp: Style::new().fg_color(Some(solarized::BASE01)),
+            message: Style::new(),
+            kv_key: Style::new().fg_color(Some(solarized::BASE01)),
+            kv_value: Style::new().fg_color(Some(solarized::CYAN)),
+            number: Style::new().fg_color(Some(solarized::MAGENTA)),
+            duration: Style::new().fg_color(Some(solarized::VIOLET)),
+            string: Style::new().fg_color(Some(solarized::GREEN)),
+            path: Style::new().fg_color(Some(solarized::CYAN)),
+            address: Style::new().fg_color(Some(solarized::ORANGE)),
+            url: Style::new().underline().fg_color(Some(solarized::BLUE)),
+        }
+    }
+
+    /// A theme without any color, only using text effects
+    pub fn monochrome() -> Self {
+        Self {
+            trace: Style::new().dimmed(),
+            debug: Style::new().dimmed(),
+            info: Style::new(),
+            warn: Style::new().bold(),
+            error: Style::new().bold().underline(),
+            target: Style::new().underline(),
+            location: Style::new(),
+            timestamp: Style::new(),
+            message: Style::new(),
+            kv_key: Style::new().italic(),
+            kv_value: Style::new(),
+            number: Style::new().bold(),
+            duration: Style::new().bold(),
+            string: Style::new().italic(),
+            path: Style::new().underline(),
+            address: Style::new().bold(),
+            url: Style::new().underline(),
+        }
+    }
+
+    /// Returns the built-in theme with the given name: `default`,
+    /// `solarized` or `monochrome`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "solarized" => Some(Self::solarized()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// A theme where everything is dimmed, for the lines not matching the
+    /// highlighting rules
+    #[cfg(feature = "regex")]
+    pub(crate) fn dimmed() -> Self {
+        let dim = Style::new().dimmed();
+        Self {
+            trace: dim,
+            debug: dim,
+            info: dim,
+            warn: dim,
+            error: dim,
+            target: dim,
+            location: dim,
+            timestamp: dim,
+            message: dim,
+            kv_key: dim,
+            kv_value: dim,
+            number: dim,
+            duration: dim,
+            string: dim,
+            path: dim,
+            address: dim,
+            url: dim,
+        }
+    }
+
+    /// Returns the style of the given level
+    #[inline]
+    pub fn level(&self, level: Level) -> Style {
+        match level {
+            Level::Trace => self.trace,
+            Level::Debug => self.debug,
+            Level::Info => self.info,
+            Level::Warn => self.warn,
+            Level::Error => self.error,
+        }
+    }
+
+    /// Overrides styles from a specification similar to `LS_COLORS`, such as
+    /// `error=1;31:target=36`.
+    ///
+    /// Keys are `trace`, `debug`, `info`, `warn`, `error`, `target`,
+    /// `location`, `time`, `msg`, `key`, `value`, `number`, `duration`,
+    /// `string`, `path`, `address` and `url`. Values are SGR parameters, as
+    /// used in ANSI escape sequences. Invalid entries are ignored.
+    ///
+    /// ```
+    /// use lovely_env_logger::env_logger::fmt::style::{AnsiColor, Style};
+    /// use lovely_env_logger::Theme;
+    ///
+    /// let mut theme = Theme::monochrome();
+    /// theme.apply_colors("error=1;31:target=36:unknown=1");
+    /// assert_eq!(theme.error, Style::new().bold().fg_color(Some(AnsiColor::Red.into())));
+    /// assert_eq!(theme.target, Style::new().fg_color(Some(AnsiColor::Cyan.into())));
+    /// ```
+    pub fn apply_colors(&mut self, spec: &str) {
+        for entry in spec.split(':') {
+            let Some((key, value)) = entry.split_once('=') else {
+                continue;
+            };
+            let Some(style) = parse_sgr(value) else {
+                continue;
+            };
+            match key.trim() {
+                "trace" => self.trace = style,
+                "debug" => self.debug = style,
+                "info" => self.info = style,
+                "warn" => self.warn = style,
+                "error" => self.error = style,
+                "target" => self.target = style,
+                "location" => self.location = style,
+                "time" => self.timestamp = style,
+                "msg" => self.message = style,
+                "key" => self.kv_key = style,
+                "value" => self.kv_value = style,
+                "number" => self.number = style,
+                "duration" => self.duration = style,
+                "string" => self.string = style,
+                "path" => self.path = style,
+                "address" => self.address = style,
+                "url" => self.url = style,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// How targets are colored
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TargetColors {
+    /// Use the target style of the theme for every target
+    #[default]
+    Off,
+    /// Give each target its own color
+    Target,
+    /// Give each crate its own color, from the first segment of the target
+    Crate,
+}
+
+impl TargetColors {
+    /// Returns the style of the given target, based on the target style of
+    /// the theme
+    pub(crate) fn style(self, theme: &Theme, target: &str) -> Style {
+        let key = match self {
+            Self::Off => return theme.target,
+            Self::Target => target,
+            Self::Crate => target.split("::").next().unwrap_or(target),
+        };
+        let color = TARGET_PALETTE[fnv1a(key.as_bytes()) as usize % TARGET_PALETTE.len()];
+        theme
+            .target
+            .fg_color(Some(Color::Ansi256(Ansi256Color(color))))
+    }
+}
+
+/// Colors of the 256 colors palette given to targets, readable on both light
+/// and dark backgrounds
+const TARGET_PALETTE: [u8; 12] = [31, 32, 33, 35, 37, 61, 67, 96, 130, 133, 166, 172];
+
+/// Hashes bytes with 32-bit FNV-1a, which is stable across runs and platforms
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Parses SGR parameters such as `1;31` or `38;5;208` into a style
+pub(crate) fn parse_sgr(value: &str) -> Option<Style> {
+    let mut codes = Vec::new();
+    for code in value.trim().split(';') {
+        if code.is_empty() {
+            codes.push(0);
+        } else {
+            codes.push(code.parse::<u8>().ok()?);
+        }
+    }
+
+    let mut style = Style::new();
+    let mut codes = codes.into_iter();
+    while let Some(code) = codes.next() {
+        style = match code {
+            0 => Style::new(),
+            1 => style.bold(),
+            2 => style.dimmed(),
+            3 => style.italic(),
+            4 => style.underline(),
+            5 => style.blink(),
+            7 => style.invert(),
+            8 => style.hidden(),
+            9 => style.strikethrough(),
+            30..=37 => style.fg_color(Some(Color::Ansi(ansi_color(code - 30, false)))),
+            38 => style.fg_color(Some(extended_color(&mut codes)?)),
+            39 => style.fg_color(None),
+            40..=47 => style.bg_color(Some(Color::Ansi(ansi_color(code - 40, false)))),
+            48 => style.bg_color(Some(extended_color(&mut codes)?)),
+            49 => style.bg_color(None),
+            90..=97 => style.fg_color(Some(Color::Ansi(ansi_color(code - 90, true)))),
+            100..=107 => style.bg_color(Some(Color::Ansi(ansi_color(code - 100, true)))),
+            _ => return None,
+        };
+    }
+    Some(style)
+}
+
+#[cfg(feature = "regex")]
+/// Parses style names separated by commas, such as `red,bold` or
+/// `bright_white,on_blue`
+pub(crate) fn parse_style_names(value: &str) -> Option<Style> {
+    let mut style = Style::new();
+    for name in value.split(',') {
+        let name = name.trim();
+        style = match name {
+            "bold" => style.bold(),
+            "dim" | "dimmed" => style.dimmed(),
+            "italic" => style.italic(),
+            "underline" => style.underline(),
+            "blink" => style.blink(),
+            "invert" | "reverse" => style.invert(),
+            "hidden" => style.hidden(),
+            "strikethrough" => style.strikethrough(),
+            _ => match name.strip_prefix("on_") {
+                Some(color) => style.bg_color(Some(color_name(color)?)),
+                None => style.fg_color(Some(color_name(name)?)),
+            },
+        };
+    }
+    Some(style)
+}
+
+#[cfg(feature = "regex")]
+/// Parses the name of a basic color, such as `red` or `bright_red`, or the
+/// index of a color of the 256 colors palette
+fn color_name(name: &str) -> Option<Color> {
+    let (name, bright) = match name.strip_prefix("bright_") {
+        Some(name) => (name, true),
+        None => (name, false),
+    };
+    let index = match name {
+        "black" => 0,
+        "red" => 1,
+        "green" => 2,
+        "yellow" => 3,
+        "blue" => 4,
+        "magenta" => 5,
+        "cyan" => 6,
+        "white" => 7,
+        _ if !bright => return Some(Color::Ansi256(Ansi256Color(name.parse().ok()?))),
+        _ => return None,
+    };
+    Some(Color::Ansi(ansi_color(index, bright)))
+}
+
+/// Returns one of the 8 basic colors, or its bright variant
+fn ansi_color(index: u8, bright: bool) -> AnsiColor {
+    let color = match index {
+        0 => AnsiColor::Black,
+        1 => AnsiColor::Red,
+        2 => AnsiColor::Green,
+        3 => AnsiColor::Yellow,
+        4 => AnsiColor::Blue,
+        5 => AnsiColor::Magenta,
+        6 => AnsiColor::Cyan,
+        _ => AnsiColor::White,
+    };
+    color.bright(bright)
+}
+
+/// Parses the arguments of the `38` and `48` SGR codes: `5;n` for a color of
+/// the 256 colors palette, or `2;r;g;b` for a RGB color
+fn extended_color(codes: &mut impl Iterator<Item = u8>) -> Option<Color> {
+    match codes.next()? {
+        5 => Some(Color::Ansi256(Ansi256Color(codes.next()?))),
+        2 => Some(Color::Rgb(RgbColor(
+            codes.next()?,
+            codes.next()?,
+            codes.next()?,
+        ))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use env_logger::fmt::style::{Ansi256Color, Color};
+
+    use super::{TargetColors, Theme, TARGET_PALETTE};
+
+    /// Returns the color given to a target
+    fn color(colors: TargetColors, target: &str) -> Option<Color> {
+        colors.style(&Theme::default(), target).get_fg_color()
+    }
+
+    #[test]
+    fn targets_always_get_the_same_color() {
+        // The hash is stable across runs and platforms
+        let expected = [("app", 0), ("app::db", 10), ("hyper", 9)];
+        for (target, index) in expected {
+            let palette = Color::Ansi256(Ansi256Color(TARGET_PALETTE[index]));
+            assert_eq!(
+                color(TargetColors::Target, target),
+                Some(palette),
+                "{target}"
+            );
+            assert_eq!(
+                color(TargetColors::Target, target),
+                color(TargetColors::Target, target)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn style_names() {
+        use env_logger::fmt::style::{AnsiColor, Style};
+
+        use super::parse_style_names;
+
+        let ansi = |color: AnsiColor| Some(Color::Ansi(color));
+        assert_eq!(
+            parse_style_names("red,bold"),
+            Some(Style::new().fg_color(ansi(AnsiColor::Red)).bold())
+        );
+        assert_eq!(
+            parse_style_names("bright_white, on_blue"),
+            Some(
+                Style::new()
+                    .fg_color(ansi(AnsiColor::BrightWhite))
+                    .bg_color(ansi(AnsiColor::Blue))
+            )
+        );
+        assert_eq!(
+            parse_style_names("on_bright_red,dim,reverse"),
+            Some(
+                Style::new()
+                    .bg_color(ansi(AnsiColor::BrightRed))
+                    .dimmed()
+                    .invert()
+            )
+        );
+        assert_eq!(
+            parse_style_names("208,on_17"),
+            Some(
+                Style::new()
+                    .fg_color(Some(Color::Ansi256(Ansi256Color(208))))
+                    .bg_color(Some(Color::Ansi256(Ansi256Color(17))))
+            )
+        );
+        assert_eq!(parse_style_names("purple"), None);
+        assert_eq!(parse_style_names("bright_208"), None);
+        assert_eq!(parse_style_names("256"), None);
+        assert_eq!(parse_style_names("red,"), None);
+    }
+
+    #[test]
+    fn crates_share_their_color() {
+        assert_eq!(
+            color(TargetColors::Crate, "app::db"),
+            color(TargetColors::Target, "app")
+        );
+        assert_eq!(
+            color(TargetColors::Crate, "app::db"),
+            color(TargetColors::Crate, "app::http")
+        );
+        assert_eq!(
+            color(TargetColors::Off, "app::db"),
+            Theme::default().target.get_fg_color()
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Format of the absolute timestamps, as set through `Config::time_format`,
+//! `Config::time_precision` and `Config::timezone`, or the
+//! `RUST_LOG_TIME_FORMAT`, `RUST_LOG_TIME_PRECISION` and `RUST_LOG_TIMEZONE`
+//! environment variables.
+
+use std::fmt::Write;
+
+#[cfg(feature = "reltime")]
+use chrono::NaiveDate;
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat, TimeZone, Utc};
+
+/// Precision of the absolute timestamps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimePrecision {
+    /// Seconds
+    Seconds,
+    /// Milliseconds
+    Millis,
+    /// Microseconds
+    Micros,
+    /// Nanoseconds
+    Nanos,
+}
+
+impl TimePrecision {
+    /// Parses `s`, `ms`, `us`, `µs` or `ns`
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "s" => Some(Self::Seconds),
+            "ms" => Some(Self::Millis),
+            "us" | "µs" => Some(Self::Micros),
+            "ns" => Some(Self::Nanos),
+            _ => None,
+        }
+    }
+}
+
+/// Time zone of the absolute timestamps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Timezone {
+    /// Coordinated Universal Time
+    Utc,
+    /// The local time zone of the system
+    Local,
+    /// A fixed offset east of UTC, in seconds
+    Fixed(i32),
+}
+
+impl Timezone {
+    /// Parses `utc`, `local`, or an offset such as `+02:00`, `-0530` or `+01`
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "utc" | "UTC" | "Z" => return Some(Self::Utc),
+            "local" => return Some(Self::Local),
+            _ => {}
+        }
+        let (sign, offset) = match value.split_at_checked(1)? {
+            ("+", offset) => (1, offset),
+            ("-", offset) => (-1, offset),
+            _ => return None,
+        };
+        let offset = offset.replace(':', "");
+        if !offset.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        let (hours, minutes) = match offset.len() {
+            2 => (offset.parse::<i32>().ok()?, 0),
+            4 => (offset[..2].parse().ok()?, offset[2..].parse().ok()?),
+            _ => return None,
+        };
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        Some(Self::Fixed(sign * (hours * 3600 + minutes * 60)))
+    }
+}
+
+/// Formats the absolute timestamps
+pub(crate) struct TimeFormatter {
+    /// strftime-like format, or RFC 3339 when not set
+    format: Option<String>,
+    /// Time zone of the timestamps
+    timezone: Timezone,
+    /// Precision of the timestamps
+    precision: TimePrecision,
+}
+
+impl TimeFormatter {
+    /// Creates a formatter, expanding `%.f` in the format to the fraction of
+    /// second matching the precision
+    pub(crate) fn new(format: Option<&str>, timezone: Timezone, precision: TimePrecision) -> Self {
+        let fraction = match precision {
+            TimePrecision::Seconds => "",
+            TimePrecision::Millis => "%.3f",
+            TimePrecision::Micros => "%.6f",
+            TimePrecision::Nanos => "%.9f",
+        };
+        Self {
+            format: format.map(|format| format.replace("%.f", fraction)),
+            timezone,
+            precision,
+        }
+    }
+
+    /// Formats a timestamp
+    pub(crate) fn format(&self, time: DateTime<Utc>) -> String {
+        match self.timezone {
+            Timezone::Utc => self.format_in(time),
+            Timezone::Local => self.format_in(time.with_timezone(&Local)),
+            Timezone::Fixed(offset) => match FixedOffset::east_opt(offset) {
+                Some(offset) => self.format_in(time.with_timezone(&offset)),
+                None => self.format_in(time),
+            },
+        }
+    }
+
+    /// Returns the day of a timestamp, in the time zone of the formatter
+    #[cfg(feature = "reltime")]
+    pub(crate) fn day(&self, time: DateTime<Utc>) -> NaiveDate {
+        match self.timezone {
+            Timezone::Utc => time.date_naive(),
+            Timezone::Local => time.with_timezone(&Local).date_naive(),
+            Timezone::Fixed(offset) => match FixedOffset::east_opt(offset) {
+                Some(offset) => time.with_timezone(&offset).date_naive(),
+                None => time.date_naive(),
+            },
+        }
+    }
+
+    /// Formats a timestamp in a given time zone
+    fn format_in<Tz: TimeZone>(&self, time: DateTime<Tz>) -> String
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        let precision = match self.precision {
+            TimePrecision::Seconds => SecondsFormat::Secs,
+            TimePrecision::Millis => SecondsFormat::Millis,
+            TimePrecision::Micros => SecondsFormat::Micros,
+            TimePrecision::Nanos => SecondsFormat::Nanos,
+        };
+        if let Some(ref format) = self.format {
+            let mut formatted = String::new();
+            // Invalid formats fall back to RFC 3339
+            if write!(formatted, "{}", time.format(format)).is_ok() {
+                return formatted;
+            }
+        }
+        time.to_rfc3339_opts(precision, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::{TimeFormatter, TimePrecision, Timezone};
+
+    /// Returns 2024-01-31T12:34:56.789012345Z
+    fn time() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 31, 12, 34, 56).unwrap()
+            + chrono::Duration::nanoseconds(789_012_345)
+    }
+
+    #[test]
+    fn precisions() {
+        assert_eq!(TimePrecision::parse("s"), Some(TimePrecision::Seconds));
+        assert_eq!(TimePrecision::parse("ms"), Some(TimePrecision::Millis));
+        assert_eq!(TimePrecision::parse("us"), Some(TimePrecision::Micros));
+        assert_eq!(TimePrecision::parse("µs"), Some(TimePrecision::Micros));
+        assert_eq!(TimePrecision::parse("ns"), Some(TimePrecision::Nanos));
+        assert_eq!(TimePrecision::parse("m"), None);
+    }
+
+    #[test]
+    fn timezones() {
+        for utc in ["utc", "UTC", "Z"] {
+            assert_eq!(Timezone::parse(utc), Some(Timezone::Utc));
+        }
+        assert_eq!(Timezone::parse("local"), Some(Timezone::Local));
+        assert_eq!(Timezone::parse("+02:00"), Some(Timezone::Fixed(7200)));
+        assert_eq!(Timezone::parse("-0530"), Some(Timezone::Fixed(-19_800)));
+        assert_eq!(Timezone::parse("+01"), Some(Timezone::Fixed(3600)));
+        assert_eq!(Timezone::parse("-00:00"), Some(Timezone::Fixed(0)));
+        assert_eq!(Timezone::parse("+23:59"), Some(Timezone::Fixed(86_340)));
+        for invalid in [
+            "",
+            "Local",
+            "02:00",
+            "+2",
+            "+1:00",
+            "+24:00",
+            "+02:60",
+            "+0a",
+            "+02:00:00",
+            "+-1",
+        ] {
+            assert_eq!(Timezone::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn formats() {
+        let rfc3339 = |timezone, precision| TimeFormatter::new(None, timezone, precision);
+        assert_eq!(
+            rfc3339(Timezone::Utc, TimePrecision::Millis).format(time()),
+            "2024-01-31T12:34:56.789Z"
+        );
+        assert_eq!(
+            rfc3339(Timezone::Fixed(-5 * 3600), TimePrecision::Nanos).format(time()),
+            "2024-01-31T07:34:56.789012345-05:00"
+        );
+
+        let formatter = TimeFormatter::new(
+            Some("%Y-%m-%d %H:%M:%S%.f %z"),
+            Timezone::Fixed(5 * 3600 + 1800),
+            TimePrecision::Micros,
+        );
+        assert_eq!(formatter.format(time()), "2024-01-31 18:04:56.789012 +0530");
+        let formatter = TimeFormatter::new(Some("%T%.f"), Timezone::Utc, TimePrecision::Seconds);
+        assert_eq!(formatter.format(time()), "12:34:56");
+    }
+
+    #[test]
+    fn invalid_formats_fall_back_to_rfc3339() {
+        let formatter = TimeFormatter::new(Some("%Q"), Timezone::Utc, TimePrecision::Seconds);
+        assert_eq!(formatter.format(time()), "2024-01-31T12:34:56Z");
+    }
+
+    #[test]
+    #[cfg(feature = "reltime")]
+    fn days_are_in_the_timezone() {
+        let late = Utc.with_ymd_and_hms(2024, 1, 31, 23, 30, 0).unwrap();
+        let day = |timezone| TimeFormatter::new(None, timezone, TimePrecision::Seconds).day(late);
+        assert_eq!(day(Timezone::Utc).to_string(), "2024-01-31");
+        assert_eq!(day(Timezone::Fixed(3600)).to_string(), "2024-02-01");
+        assert_eq!(day(Timezone::Fixed(-3600)).to_string(), "2024-01-31");
+    }
+}
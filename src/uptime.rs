@@ -0,0 +1,90 @@
+//! Timestamps relative to the start of the logger, as set through
+//! `Config::with_uptime_timestamp` or the `RUST_LOG_WITH_UPTIME_TIMESTAMPS`
+//! environment variable.
+
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Instant of the last call to `reset_uptime()`, if any
+static RESET: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Resets the origin of the uptime timestamps of every logger to now, such as
+/// at the start of each phase of a benchmark.
+///
+/// ```
+/// lovely_env_logger::init(lovely_env_logger::Config {
+///     with_uptime_timestamp: true,
+///     ..Default::default()
+/// });
+/// log::info!("warming up");
+/// lovely_env_logger::reset_uptime();
+/// log::info!("first phase");
+/// ```
+pub fn reset_uptime() {
+    *RESET.lock().unwrap() = Some(Instant::now());
+}
+
+/// Returns the time elapsed since the start of a logger, or since the last
+/// call to `reset_uptime()` when it happened after the start
+pub(crate) fn uptime(start: Instant) -> Uptime {
+    Uptime(origin(start).elapsed())
+}
+
+/// Returns the start of a logger, or the last call to `reset_uptime()` when it
+/// happened after the start
+fn origin(start: Instant) -> Instant {
+    match *RESET.lock().unwrap() {
+        Some(reset) if reset > start => reset,
+        _ => start,
+    }
+}
+
+/// Time elapsed since the origin of the uptime timestamps, displayed as
+/// `[   12.345678]`
+pub(crate) struct Uptime(Duration);
+
+impl fmt::Display for Uptime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{:>5}.{:0>6}]",
+            self.0.as_secs(),
+            self.0.subsec_micros()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::Uptime;
+
+    #[test]
+    fn display() {
+        assert_eq!(Uptime(Duration::ZERO).to_string(), "[    0.000000]");
+        assert_eq!(
+            Uptime(Duration::from_micros(1_100_000)).to_string(),
+            "[    1.100000]"
+        );
+        assert_eq!(
+            Uptime(Duration::from_nanos(123_456_789_999)).to_string(),
+            "[  123.456789]"
+        );
+    }
+
+    #[test]
+    fn reset_uptime() {
+        // The only test resetting the uptime, which is shared by every logger
+        let start = Instant::now() - Duration::from_secs(1);
+        assert!(super::uptime(start).0 >= Duration::from_secs(1));
+
+        let before = Instant::now();
+        super::reset_uptime();
+        let after = Instant::now();
+        let origin = super::origin(start);
+        assert!(before <= origin && origin <= after);
+        assert_eq!(super::origin(after), after);
+    }
+}
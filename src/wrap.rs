@@ -0,0 +1,102 @@
+//! Wrapping of long messages at the width of the terminal, as set through
+//! `Config::wrap` or the `RUST_LOG_WRAP` environment variable.
+
+/// Returns the width of the terminal standard error is connected to, or the
+/// value of `COLUMNS` when set
+#[cfg(feature = "wrap")]
+pub(crate) fn terminal_width() -> Option<usize> {
+    if let Some(columns) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns| columns > 0)
+    {
+        return Some(columns);
+    }
+    #[cfg(unix)]
+    {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        // SAFETY: TIOCGWINSZ only writes to the given winsize structure
+        if unsafe { libc::ioctl(libc::STDERR_FILENO, libc::TIOCGWINSZ, &mut size) } == 0
+            && size.ws_col > 0
+        {
+            return Some(size.ws_col.into());
+        }
+    }
+    None
+}
+
+/// Splits a line into pieces of at most `width` characters, at word
+/// boundaries when possible.
+///
+/// The spaces where the line is split are dropped. Words longer than `width`
+/// are split in the middle.
+pub(crate) fn wrap(line: &str, width: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = line;
+    while rest.chars().count() > width {
+        // Byte index of the first character not fitting in the piece
+        let end = rest
+            .char_indices()
+            .nth(width)
+            .map_or(rest.len(), |(index, _)| index);
+        let space = if rest[end..].starts_with(' ') {
+            Some(end)
+        } else {
+            rest[..end].rfind(' ')
+        };
+        match space.filter(|&space| !rest[..space].trim_end_matches(' ').is_empty()) {
+            Some(space) => {
+                pieces.push(rest[..space].trim_end_matches(' '));
+                rest = rest[space..].trim_start_matches(' ');
+            }
+            None => {
+                pieces.push(&rest[..end]);
+                rest = &rest[end..];
+            }
+        }
+    }
+    if !rest.is_empty() || pieces.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::wrap;
+
+    #[test]
+    fn short_lines_are_kept() {
+        assert_eq!(wrap("", 10), [""]);
+        assert_eq!(wrap("hello", 10), ["hello"]);
+        assert_eq!(wrap("hello", 5), ["hello"]);
+    }
+
+    #[test]
+    fn words_are_kept_whole() {
+        assert_eq!(
+            wrap("the quick brown fox jumps", 10),
+            ["the quick", "brown fox", "jumps"]
+        );
+    }
+
+    #[test]
+    fn long_words_are_split() {
+        assert_eq!(wrap("abcdefghijklm", 5), ["abcde", "fghij", "klm"]);
+        assert_eq!(wrap("a verylongword b", 5), ["a", "veryl", "ongwo", "rd b"]);
+    }
+
+    #[test]
+    fn runs_of_spaces_are_dropped_at_splits() {
+        assert_eq!(wrap("aaa     bbb", 5), ["aaa", "bbb"]);
+        assert_eq!(wrap("abc   ", 3), ["abc"]);
+        assert_eq!(wrap("a  b  c", 10), ["a  b  c"]);
+    }
+
+    #[test]
+    fn multibyte_characters() {
+        assert_eq!(wrap("héllo wörld ça", 6), ["héllo", "wörld", "ça"]);
+        assert_eq!(wrap("ééééé", 2), ["éé", "éé", "é"]);
+        assert_eq!(wrap("│ ── │", 3), ["│", "──", "│"]);
+    }
+}